@@ -1,5 +1,5 @@
 use crossterm::style::{Attribute, Color, ContentStyle, Stylize};
-use pager_rs::{CommandList, State, StatusBar};
+use pager_rs::{CommandList, Pager, State, StatusBar};
 fn main() -> std::io::Result<()> {
     let content = r#"fn main() {
     println!("Hello World!");
@@ -17,11 +17,7 @@ fn main() -> std::io::Result<()> {
 
     let mut state = State::new(content, status_bar, CommandList::default())?;
 
-    pager_rs::init()?;
-
-    pager_rs::run(&mut state)?;
-
-    pager_rs::finish()?;
+    Pager::new()?.run(&mut state)?;
 
     Ok(())
 }
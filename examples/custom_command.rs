@@ -1,5 +1,7 @@
 use crossterm::event::KeyCode;
-use pager_rs::{run, Command, CommandList, CommandType, State, StatusBar};
+use pager_rs::{
+    complete_path, run, Command, CommandList, CommandType, Pager, ReaderSource, State, StatusBar,
+};
 fn main() -> std::io::Result<()> {
     let content = r#"Lorem ipsum dolor sit amet, consectetur adipiscing
 elit. Pellentesque neque nulla, viverra ac sapien
@@ -18,20 +20,24 @@ Maecenas in magna tempus, porta augue bibendum, feugiat nulla."#
     let status_bar =
         StatusBar::new("Press 'p' to open selected line on seperate instance".to_string());
 
+    // Commands can capture and modify local values.
+    let mut opened = 0;
+
     let mut state = State::new(
         content,
         status_bar,
         CommandList::combine(vec![
-            CommandList(vec![Command {
-                cmd: vec![CommandType::Key(KeyCode::Char('p'))],
-                desc: "Open selected line on seperate instance".to_string(),
-                func: &|state| {
+            CommandList(vec![Command::new(
+                vec![CommandType::Key(KeyCode::Char('p'))],
+                "Open selected line on seperate instance".to_string(),
+                move |state| {
+                    opened += 1;
                     let commands =
                         CommandList::combine(vec![CommandList::quit(), CommandList::navigation()]);
 
                     let mut modal = State::new(
-                        state.content.lines().nth(state.pos.1).unwrap().to_string(),
-                        StatusBar::new("Quit (q)".to_string()),
+                        state.content.line(state.pos.1).unwrap().to_string(),
+                        StatusBar::new(format!("Quit (q), opened {} times", opened)),
                         commands,
                     )
                     .unwrap();
@@ -39,7 +45,35 @@ Maecenas in magna tempus, porta augue bibendum, feugiat nulla."#
                     run(&mut modal).unwrap();
                     true
                 },
-            }]),
+            )]),
+            CommandList(vec![Command::with_args(
+                vec![CommandType::Colon("open".to_string())],
+                "Open a file on seperate instance, e.g. `:open Cargo.toml`".to_string(),
+                |_, args| {
+                    args.expect_len(1, 1)?;
+                    let path = args.get(0).unwrap();
+                    let content = ReaderSource::file(path).map_err(|err| err.to_string())?;
+
+                    let mut modal = State::new(
+                        content,
+                        StatusBar::new(format!("{} - Quit (q)", path)),
+                        CommandList::combine(vec![CommandList::quit(), CommandList::navigation()]),
+                    )
+                    .map_err(|err| err.to_string())?;
+                    run(&mut modal).map_err(|err| err.to_string())?;
+                    Ok(true)
+                },
+            )
+            .with_completer(|_, _, partial| complete_path(partial))]),
+            // Key sequences and modifiers, `zl` or `Ctrl-l`.
+            CommandList(vec![Command::new(
+                vec![CommandType::sequence("zl"), CommandType::ctrl('l')],
+                "Toggle line numbers".to_string(),
+                |state| {
+                    state.show_line_numbers = !state.show_line_numbers;
+                    true
+                },
+            )]),
             CommandList::quit(),
             CommandList::navigation(),
             CommandList::help(),
@@ -47,11 +81,7 @@ Maecenas in magna tempus, porta augue bibendum, feugiat nulla."#
     )?;
     state.show_line_numbers = false;
 
-    pager_rs::init()?;
-
-    pager_rs::run(&mut state)?;
-
-    pager_rs::finish()?;
+    Pager::new()?.run(&mut state)?;
 
     Ok(())
 }
@@ -0,0 +1,22 @@
+use pager_rs::{CommandList, Pager, ReaderSource, State, StatusBar};
+use std::env;
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() >= 2 {
+        let file_name = args[1].clone();
+
+        let content = ReaderSource::follow_file(file_name.clone())?;
+
+        let status_bar = StatusBar::new(format!("{} (press 'F' to follow)", file_name));
+
+        let mut state = State::new(content, status_bar, CommandList::default())?;
+        state.follow();
+
+        Pager::new()?.run(&mut state)?;
+    } else {
+        eprintln!("Missing Filename");
+    }
+
+    Ok(())
+}
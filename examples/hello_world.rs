@@ -1,4 +1,4 @@
-use pager_rs::{CommandList, State, StatusBar};
+use pager_rs::{CommandList, Pager, State, StatusBar};
 fn main() -> std::io::Result<()> {
     let content = r#"fn main() {
     println!("Hello World!");
@@ -9,11 +9,7 @@ fn main() -> std::io::Result<()> {
 
     let mut state = State::new(content, status_bar, CommandList::default())?;
 
-    pager_rs::init()?;
-
-    pager_rs::run(&mut state)?;
-
-    pager_rs::finish()?;
+    Pager::new()?.run(&mut state)?;
 
     Ok(())
 }
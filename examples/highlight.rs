@@ -0,0 +1,30 @@
+use pager_rs::{CommandList, HighlightedSource, MmapSource, Pager, State, StatusBar};
+use std::env;
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() >= 2 {
+        let file_name = args[1].clone();
+
+        let mut content = HighlightedSource::for_path(MmapSource::open(&file_name)?, &file_name);
+        if let Some(theme) = args.get(2) {
+            if !content.set_theme(theme) {
+                eprintln!(
+                    "Unknown theme, available themes: {}",
+                    HighlightedSource::<MmapSource>::theme_names().join(", ")
+                );
+                return Ok(());
+            }
+        }
+
+        let status_bar = StatusBar::new(format!("{} [{}]", file_name, content.syntax_name()));
+
+        let mut state = State::new(content, status_bar, CommandList::default())?;
+
+        Pager::new()?.run(&mut state)?;
+    } else {
+        eprintln!("Usage: highlight <file> [theme]");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use pager_rs::{CommandList, Pager, ReaderSource, State, StatusBar};
+use std::env;
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() >= 3 {
+        let commands = match args[1].as_str() {
+            "less" => CommandList::less(),
+            "vi" => CommandList::vi(),
+            "emacs" => CommandList::emacs(),
+            keymap => {
+                eprintln!("Unknown keymap: {}", keymap);
+                return Ok(());
+            }
+        };
+        let file_name = args[2].clone();
+
+        let content = ReaderSource::file(file_name.clone())?;
+
+        let status_bar = StatusBar::new(format!("{} ({})", file_name, args[1]));
+
+        let mut state = State::new(content, status_bar, commands)?;
+
+        Pager::new()?.run(&mut state)?;
+    } else {
+        eprintln!("Usage: keymap <less|vi|emacs> <file>");
+    }
+
+    Ok(())
+}
@@ -1,24 +1,18 @@
-use pager_rs::{CommandList, State, StatusBar};
-use std::{env, fs::File, io::Read};
+use pager_rs::{CommandList, MmapSource, Pager, State, StatusBar};
+use std::env;
 fn main() -> std::io::Result<()> {
     let args: Vec<String> = env::args().collect();
 
     if args.len() >= 2 {
         let file_name = args[1].clone();
 
-        let mut file = File::open(file_name.clone())?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
+        let content = MmapSource::open(&file_name)?;
 
         let status_bar = StatusBar::new(file_name);
 
         let mut state = State::new(content, status_bar, CommandList::default())?;
 
-        pager_rs::init()?;
-
-        pager_rs::run(&mut state)?;
-
-        pager_rs::finish()?;
+        Pager::new()?.run(&mut state)?;
     } else {
         eprintln!("Missing Filename");
     }
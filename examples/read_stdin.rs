@@ -0,0 +1,10 @@
+use pager_rs::{page, CommandList, ReaderSource, StatusBar};
+fn main() -> std::io::Result<()> {
+    // Try: `cargo build 2>&1 | cargo run --example read_stdin`
+    // When the output is redirected too, the content is printed as it is read.
+    let content = ReaderSource::stdin();
+
+    let status_bar = StatusBar::new("stdin".to_string());
+
+    page(content, status_bar, CommandList::default())
+}
@@ -0,0 +1,406 @@
+//! Parser for ANSI escape sequences in content.
+//!
+//! SGR sequences (`ESC [ ... m`) are turned into [`Span`] styles,
+//! other CSI, OSC and two-byte escape sequences are dropped.
+
+use std::borrow::Cow;
+
+use crossterm::style::{Attribute, Color, ContentStyle};
+
+use crate::Span;
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+/// Parse `line` into styled spans of text without escape sequences.
+pub(crate) fn parse(line: &str) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut style = ContentStyle::new();
+    let mut text = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            text.push(c);
+            continue;
+        }
+        match chars.peek() {
+            Some('[') => {
+                chars.next();
+                let mut params = String::new();
+                let mut terminator = None;
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        terminator = Some(c);
+                        break;
+                    }
+                    params.push(c);
+                }
+                if terminator == Some('m') {
+                    let new_style = apply_sgr(style, &params);
+                    if new_style != style {
+                        if !text.is_empty() {
+                            spans.push(Span::new(std::mem::take(&mut text), style));
+                        }
+                        style = new_style;
+                    }
+                }
+            }
+            Some(']') => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == BEL {
+                        break;
+                    }
+                    if c == ESC && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            Some(_) => {
+                chars.next();
+            }
+            // Lone escape at the end of the line is kept as is.
+            None => text.push(c),
+        }
+    }
+    if !text.is_empty() || spans.is_empty() {
+        spans.push(Span::new(text, style));
+    }
+    spans
+}
+
+/// Remove escape sequences from `line`.
+pub(crate) fn strip(line: &str) -> Cow<'_, str> {
+    if !line.contains(ESC) {
+        return Cow::Borrowed(line);
+    }
+    Cow::Owned(parse(line).into_iter().map(|span| span.text).collect())
+}
+
+/// Underline attributes, only one of them is shown at a time.
+const UNDERLINES: [Attribute; 5] = [
+    Attribute::Underlined,
+    Attribute::DoubleUnderlined,
+    Attribute::Undercurled,
+    Attribute::Underdotted,
+    Attribute::Underdashed,
+];
+
+/// Apply parameters of a SGR sequence to `style`.
+///
+/// Parameters are separated by `;`, sub-parameters of a parameter by `:`, e.g. `4:3` for a curly
+/// underline or `38:2::r:g:b` for a RGB color.
+fn apply_sgr(mut style: ContentStyle, params: &str) -> ContentStyle {
+    let mut groups = params.split(';');
+
+    while let Some(group) = groups.next() {
+        let mut sub_params = group.split(':').map(number);
+        let param = sub_params.next().unwrap_or(0);
+        match param {
+            0 => style = ContentStyle::new(),
+            1 => style.attributes.set(Attribute::Bold),
+            2 => style.attributes.set(Attribute::Dim),
+            3 => style.attributes.set(Attribute::Italic),
+            4 => {
+                let underline = match sub_params.next() {
+                    None | Some(1) => Some(Attribute::Underlined),
+                    Some(kind @ 2..=5) => Some(UNDERLINES[kind as usize - 1]),
+                    _ => None,
+                };
+                set_underline(&mut style, underline);
+            }
+            5 => style.attributes.set(Attribute::SlowBlink),
+            6 => style.attributes.set(Attribute::RapidBlink),
+            7 => style.attributes.set(Attribute::Reverse),
+            8 => style.attributes.set(Attribute::Hidden),
+            9 => style.attributes.set(Attribute::CrossedOut),
+            20 => style.attributes.set(Attribute::Fraktur),
+            21 => set_underline(&mut style, Some(Attribute::DoubleUnderlined)),
+            22 => {
+                style.attributes.unset(Attribute::Bold);
+                style.attributes.unset(Attribute::Dim);
+            }
+            23 => {
+                style.attributes.unset(Attribute::Italic);
+                style.attributes.unset(Attribute::Fraktur);
+            }
+            24 => set_underline(&mut style, None),
+            25 => {
+                style.attributes.unset(Attribute::SlowBlink);
+                style.attributes.unset(Attribute::RapidBlink);
+            }
+            27 => style.attributes.unset(Attribute::Reverse),
+            28 => style.attributes.unset(Attribute::Hidden),
+            29 => style.attributes.unset(Attribute::CrossedOut),
+            30..=37 => style.foreground_color = Some(basic_color(param - 30)),
+            38 => style.foreground_color = extended_color(group, &mut groups),
+            39 => style.foreground_color = None,
+            40..=47 => style.background_color = Some(basic_color(param - 40)),
+            48 => style.background_color = extended_color(group, &mut groups),
+            49 => style.background_color = None,
+            51 => style.attributes.set(Attribute::Framed),
+            52 => style.attributes.set(Attribute::Encircled),
+            53 => style.attributes.set(Attribute::OverLined),
+            54 => {
+                style.attributes.unset(Attribute::Framed);
+                style.attributes.unset(Attribute::Encircled);
+            }
+            55 => style.attributes.unset(Attribute::OverLined),
+            58 => style.underline_color = extended_color(group, &mut groups),
+            59 => style.underline_color = None,
+            90..=97 => style.foreground_color = Some(basic_color(param - 90 + 8)),
+            100..=107 => style.background_color = Some(basic_color(param - 100 + 8)),
+            _ => {}
+        }
+    }
+    style
+}
+
+/// Value of a parameter, empty parameters are `0`, so `ESC [ m` is the same as `ESC [ 0 m`.
+fn number(param: &str) -> u16 {
+    match param {
+        "" => 0,
+        param => param.parse().unwrap_or(u16::MAX),
+    }
+}
+
+/// Replace the underline of `style` with `underline`.
+fn set_underline(style: &mut ContentStyle, underline: Option<Attribute>) {
+    for attribute in UNDERLINES {
+        style.attributes.unset(attribute);
+    }
+    if let Some(underline) = underline {
+        style.attributes.set(underline);
+    }
+}
+
+/// Color of the 16 color palette.
+fn basic_color(index: u16) -> Color {
+    match index {
+        0 => Color::Black,
+        1 => Color::DarkRed,
+        2 => Color::DarkGreen,
+        3 => Color::DarkYellow,
+        4 => Color::DarkBlue,
+        5 => Color::DarkMagenta,
+        6 => Color::DarkCyan,
+        7 => Color::Grey,
+        8 => Color::DarkGrey,
+        9 => Color::Red,
+        10 => Color::Green,
+        11 => Color::Yellow,
+        12 => Color::Blue,
+        13 => Color::Magenta,
+        14 => Color::Cyan,
+        _ => Color::White,
+    }
+}
+
+/// Parse the color of a `38`, `48` or `58` parameter `group`.
+///
+/// The color is either in the sub-parameters, `5:n` or `2:[id]:r:g:b`, or in the next
+/// parameters of `groups`, `5;n` or `2;r;g;b`.
+fn extended_color<'p>(group: &str, groups: &mut impl Iterator<Item = &'p str>) -> Option<Color> {
+    let params: Vec<u16> = if group.contains(':') {
+        let mut params: Vec<u16> = group.split(':').skip(1).map(number).collect();
+        if params.first() == Some(&2) && params.len() > 4 {
+            // Skip the color space id.
+            params.remove(1);
+        }
+        params
+    } else {
+        let mut next = || groups.next().map(number);
+        match next()? {
+            5 => vec![5, next()?],
+            2 => vec![2, next()?, next()?, next()?],
+            _ => return None,
+        }
+    };
+    let byte = |i: usize| params.get(i).and_then(|&param| u8::try_from(param).ok());
+    match params.first()? {
+        // Indexes below 16 are the basic colors, crossterm prints those this way too.
+        5 => byte(1).map(|index| match index {
+            0..=15 => basic_color(index.into()),
+            index => Color::AnsiValue(index),
+        }),
+        2 => Some(Color::Rgb {
+            r: byte(1)?,
+            g: byte(2)?,
+            b: byte(3)?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Stylize;
+
+    use super::*;
+
+    fn style(params: &str) -> ContentStyle {
+        apply_sgr(ContentStyle::new(), params)
+    }
+
+    /// Text and style of each span of `line`.
+    fn spans(line: &str) -> Vec<(String, ContentStyle)> {
+        parse(line)
+            .into_iter()
+            .map(|span| (span.text, span.style))
+            .collect()
+    }
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(spans("plain"), [("plain".to_string(), ContentStyle::new())]);
+        assert_eq!(spans(""), [(String::new(), ContentStyle::new())]);
+        assert!(matches!(strip("plain"), Cow::Borrowed("plain")));
+    }
+
+    #[test]
+    fn styles_and_reset() {
+        let red = ContentStyle::new().with(Color::DarkRed);
+        assert_eq!(
+            spans("a\x1b[31mb\x1b[1mc\x1b[0md\x1b[me"),
+            [
+                ("a".to_string(), ContentStyle::new()),
+                ("b".to_string(), red),
+                ("c".to_string(), red.attribute(Attribute::Bold)),
+                ("de".to_string(), ContentStyle::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn sequences_that_change_nothing_keep_one_span() {
+        assert_eq!(spans("a\x1b[39mb\x1b[2Kc").len(), 1);
+        assert_eq!(strip("a\x1b[39mb\x1b[2Kc"), "abc");
+    }
+
+    #[test]
+    fn basic_colors() {
+        assert_eq!(style("32").foreground_color, Some(Color::DarkGreen));
+        assert_eq!(style("44").background_color, Some(Color::DarkBlue));
+        assert_eq!(style("97").foreground_color, Some(Color::White));
+        assert_eq!(style("100").background_color, Some(Color::DarkGrey));
+        assert_eq!(style("31;39").foreground_color, None);
+        assert_eq!(style("41;49").background_color, None);
+    }
+
+    #[test]
+    fn extended_colors() {
+        assert_eq!(style("38;5;9").foreground_color, Some(Color::Red));
+        assert_eq!(
+            style("38;5;100").foreground_color,
+            Some(Color::AnsiValue(100))
+        );
+        assert_eq!(
+            style("48;2;1;2;3").background_color,
+            Some(Color::Rgb { r: 1, g: 2, b: 3 })
+        );
+        assert_eq!(style("58;5;1").underline_color, Some(Color::DarkRed));
+        // Parameters after the color are applied too.
+        assert!(style("38;5;1;1").attributes.has(Attribute::Bold));
+    }
+
+    #[test]
+    fn invalid_colors_are_dropped() {
+        assert_eq!(style("38;5;256").foreground_color, None);
+        assert_eq!(style("38;2;1;2").foreground_color, None);
+        assert_eq!(style("38;3;1").foreground_color, None);
+        assert_eq!(style("38").foreground_color, None);
+    }
+
+    #[test]
+    fn attributes_and_their_resets() {
+        let all = style("1;2;3;5;7;8;9;53");
+        for attribute in [
+            Attribute::Bold,
+            Attribute::Dim,
+            Attribute::Italic,
+            Attribute::SlowBlink,
+            Attribute::Reverse,
+            Attribute::Hidden,
+            Attribute::CrossedOut,
+            Attribute::OverLined,
+        ] {
+            assert!(all.attributes.has(attribute));
+        }
+        let reset = apply_sgr(all, "22;23;25;27;28;29;55");
+        assert!(reset.attributes.is_empty());
+    }
+
+    #[test]
+    fn unknown_and_invalid_parameters_are_ignored() {
+        assert_eq!(style("1000;x;60"), ContentStyle::new());
+        assert!(style("x;1").attributes.has(Attribute::Bold));
+    }
+
+    #[test]
+    fn other_sequences_are_dropped() {
+        // OSC 8 hyperlink, ended by `ESC \` and by `BEL`.
+        assert_eq!(strip("\x1b]8;;http://a\x1b\\link\x1b]8;;\x07!"), "link!");
+        // Cursor movement and a two-byte escape sequence.
+        assert_eq!(strip("a\x1b[2Ab\x1b=c"), "abc");
+    }
+
+    #[test]
+    fn lone_escape_is_kept() {
+        assert_eq!(strip("a\x1b"), "a\x1b");
+        // An unterminated sequence is dropped until the end of the line.
+        assert_eq!(strip("a\x1b[31"), "a");
+    }
+
+    #[test]
+    fn underline_kinds() {
+        assert!(style("4:3").attributes.has(Attribute::Undercurled));
+        assert!(style("4").attributes.has(Attribute::Underlined));
+        assert!(style("4:3;4:0").attributes.is_empty());
+
+        // A new underline replaces the previous one.
+        let double = style("4:3;21");
+        assert!(double.attributes.has(Attribute::DoubleUnderlined));
+        assert!(!double.attributes.has(Attribute::Undercurled));
+    }
+
+    #[test]
+    fn colors_in_sub_parameters() {
+        let rgb = Some(Color::Rgb { r: 1, g: 2, b: 3 });
+        assert_eq!(style("38:2::1:2:3").foreground_color, rgb);
+        assert_eq!(style("38:2:1:2:3").foreground_color, rgb);
+        assert_eq!(
+            style("48:5:200").background_color,
+            Some(Color::AnsiValue(200))
+        );
+        assert_eq!(style("58:2:0:1:2:3").underline_color, rgb);
+        // Sub-parameters don't take the following parameters.
+        assert_eq!(style("38:5;1").foreground_color, None);
+        assert!(style("38:5;1").attributes.has(Attribute::Bold));
+    }
+
+    #[test]
+    fn fraktur_framed_and_encircled() {
+        assert!(style("20").attributes.has(Attribute::Fraktur));
+        assert!(style("3;20;23").attributes.is_empty());
+        assert!(style("51").attributes.has(Attribute::Framed));
+        assert!(style("52").attributes.has(Attribute::Encircled));
+        assert!(style("51;52;54").attributes.is_empty());
+    }
+
+    #[test]
+    fn parse_sub_parameters() {
+        let spans = parse("\x1b[4:3ma\x1b[38:2::1:2:3mb\x1b[20;38;5;200mc\x1b[4:0;23md");
+        let texts: Vec<&str> = spans.iter().map(|span| span.text.as_str()).collect();
+        assert_eq!(texts, ["a", "b", "c", "d"]);
+        assert!(spans[0].style.attributes.has(Attribute::Undercurled));
+        assert_eq!(
+            spans[1].style.foreground_color,
+            Some(Color::Rgb { r: 1, g: 2, b: 3 })
+        );
+        assert!(spans[2].style.attributes.has(Attribute::Fraktur));
+        assert_eq!(spans[2].style.foreground_color, Some(Color::AnsiValue(200)));
+        assert!(spans[3].style.attributes.is_empty());
+    }
+}
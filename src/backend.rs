@@ -0,0 +1,357 @@
+use std::{
+    collections::VecDeque,
+    io::{self, stdout, Write},
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    queue,
+    style::{ContentStyle, Print, PrintStyledContent},
+    terminal::{self, ClearType},
+};
+
+use crate::{Buffer, Cell};
+
+/// Screen that [`run`](crate::run) draws on and reads events from.
+///
+/// See: [`CrosstermBackend`], [`TestBackend`], [`State::with_backend`](crate::State::with_backend)
+pub trait Backend {
+    /// Size of the screen.
+    ///
+    /// `(width, height)`
+    fn size(&self) -> io::Result<(u16, u16)>;
+
+    /// Returns true if an event is available within `timeout`.
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+
+    /// Wait for the next event.
+    fn read(&mut self) -> io::Result<Event>;
+
+    /// Draw `buffer` on the screen, only the cells that changed since the last draw
+    /// need to be drawn.
+    ///
+    /// Rows in `scroll_region` can be moved together by scrolling the terminal,
+    /// e.g. when the view moves by a few lines.
+    fn draw(&mut self, buffer: &Buffer, scroll_region: Range<u16>) -> io::Result<()>;
+
+    /// Show the cursor at `(column, row)`, or hide it if `None`.
+    fn set_cursor(&mut self, position: Option<(u16, u16)>) -> io::Result<()>;
+
+    /// Show what is drawn since the last flush.
+    fn flush(&mut self) -> io::Result<()>;
+
+    /// Start reading keys without waiting for Enter.
+    ///
+    /// Calls are nested, input is restored after the same number of
+    /// [`Backend::disable_raw_mode`] calls.
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Undo [`Backend::enable_raw_mode`].
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Depth of [`Backend::enable_raw_mode`] calls, shared by all [`CrosstermBackend`]s
+/// since they use the same terminal.
+static RAW_MODE_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+/// Longest gap of unchanged cells that [`CrosstermBackend`] prints over instead of moving the
+/// cursor.
+const MAX_GAP: u16 = 4;
+
+/// The last [`Buffer`] drawn by a [`CrosstermBackend`], shared since they use the same terminal.
+static LAST_FRAME: Mutex<Option<Buffer>> = Mutex::new(None);
+
+/// [`Backend`] that draws on the terminal with `crossterm`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CrosstermBackend;
+
+impl CrosstermBackend {
+    /// Draw the whole screen on the next [`Backend::draw`], e.g. after leaving the
+    /// alternate screen.
+    pub(crate) fn forget_screen() {
+        *LAST_FRAME.lock().unwrap_or_else(|err| err.into_inner()) = None;
+    }
+
+    /// Disable raw mode however many times it is enabled, e.g. after a panic.
+    pub(crate) fn reset_raw_mode() -> io::Result<()> {
+        RAW_MODE_DEPTH.store(0, Ordering::SeqCst);
+        terminal::disable_raw_mode()
+    }
+}
+
+fn print_segment(
+    out: &mut impl Write,
+    (column, row): (u16, u16),
+    segment: &str,
+    style: ContentStyle,
+) -> io::Result<()> {
+    if segment.is_empty() {
+        return Ok(());
+    }
+    queue!(
+        out,
+        cursor::MoveTo(column, row),
+        PrintStyledContent(style.apply(segment))
+    )
+}
+
+impl Backend for CrosstermBackend {
+    fn size(&self) -> io::Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        event::read()
+    }
+
+    fn draw(&mut self, buffer: &Buffer, scroll_region: Range<u16>) -> io::Result<()> {
+        let mut out = stdout();
+        let mut last = LAST_FRAME.lock().unwrap_or_else(|err| err.into_inner());
+        let previous = match last.take() {
+            Some(mut previous) if previous.size() == buffer.size() => {
+                if let Some(amount) = buffer.scroll_from(&previous, scroll_region.clone()) {
+                    // Limit scrolling to the region with DECSTBM, then reset it.
+                    queue!(
+                        out,
+                        Print(format!(
+                            "\x1b[{};{}r",
+                            scroll_region.start + 1,
+                            scroll_region.end
+                        ))
+                    )?;
+                    match amount > 0 {
+                        true => queue!(out, terminal::ScrollUp(amount as u16))?,
+                        false => queue!(out, terminal::ScrollDown(-amount as u16))?,
+                    }
+                    queue!(out, Print("\x1b[r"))?;
+                    previous.scroll(scroll_region, amount);
+                }
+                previous
+            }
+            _ => {
+                queue!(out, terminal::Clear(ClearType::All))?;
+                let (width, height) = buffer.size();
+                Buffer::new(width, height)
+            }
+        };
+
+        // Print runs of changed cells that have the same style. Short gaps of unchanged cells
+        // are printed too, since moving the cursor over them takes more bytes.
+        let mut segment = String::new();
+        let mut start = (0, 0);
+        let mut end = (0, 0);
+        let mut style = ContentStyle::new();
+        for (column, row, cell) in buffer.diff(&previous) {
+            let gap = (end.0..column).filter_map(|column| buffer.cell(column, row));
+            if !segment.is_empty()
+                && row == end.1
+                && column >= end.0
+                && column - end.0 <= MAX_GAP
+                && gap.clone().all(|cell| cell.style == style)
+            {
+                segment.extend(gap.map(|cell| cell.symbol.as_str()));
+                end = (column, row);
+            }
+            if segment.is_empty() || end != (column, row) || style != cell.style {
+                print_segment(&mut out, start, &segment, style)?;
+                segment.clear();
+                start = (column, row);
+                style = cell.style;
+            }
+            segment.push_str(&cell.symbol);
+            end = (column + 1, row);
+        }
+        print_segment(&mut out, start, &segment, style)?;
+
+        *last = Some(buffer.clone());
+        Ok(())
+    }
+
+    fn set_cursor(&mut self, position: Option<(u16, u16)>) -> io::Result<()> {
+        match position {
+            Some((column, row)) => queue!(stdout(), cursor::MoveTo(column, row), cursor::Show),
+            None => queue!(stdout(), cursor::Hide),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        stdout().flush()
+    }
+
+    fn enable_raw_mode(&mut self) -> io::Result<()> {
+        if RAW_MODE_DEPTH.fetch_add(1, Ordering::SeqCst) == 0 {
+            terminal::enable_raw_mode()?;
+        }
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> io::Result<()> {
+        let depth =
+            RAW_MODE_DEPTH.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+        if depth == Ok(1) {
+            terminal::disable_raw_mode()?;
+        }
+        Ok(())
+    }
+}
+
+/// [`Backend`] that draws on a grid in memory, and reads scripted events.
+///
+/// Events are read in the order they are pushed, when they run out [`run`](crate::run)
+/// returns an [`io::ErrorKind::UnexpectedEof`] error. So scripts usually end with a key
+/// that quits.
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use pager_rs::{run, CommandList, State, StatusBar, TestBackend};
+/// let backend = Rc::new(RefCell::new(TestBackend::new(20, 3)));
+/// backend.borrow_mut().push_keys("jq");
+///
+/// let mut state = State::with_backend(
+///     "one\ntwo\nthree".to_string(),
+///     StatusBar::new("Title".to_string()),
+///     CommandList::less(),
+///     backend.clone(),
+/// )?;
+/// state.show_line_numbers = false;
+/// run(&mut state)?;
+///
+/// let backend = backend.borrow();
+/// assert_eq!(backend.frames()[0][0], "one");
+/// assert_eq!(backend.screen()[0], "two");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct TestBackend {
+    buffer: Buffer,
+    /// Number of cells changed by the last draw.
+    drawn_cells: usize,
+    cursor: Option<(u16, u16)>,
+    events: VecDeque<Event>,
+    frames: Vec<Vec<String>>,
+}
+
+impl TestBackend {
+    /// Create a [`TestBackend`] with an empty screen of `width` and `height`.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            buffer: Buffer::new(width, height),
+            drawn_cells: 0,
+            cursor: None,
+            events: VecDeque::new(),
+            frames: vec![],
+        }
+    }
+
+    /// Add an event to the end of the script.
+    ///
+    /// [`Event::Resize`] changes the size of the screen when it is read.
+    pub fn push_event(&mut self, event: Event) {
+        self.events.push_back(event);
+    }
+
+    /// Add a key press to the end of the script.
+    pub fn push_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        self.push_event(Event::Key(KeyEvent::new(code, modifiers)));
+    }
+
+    /// Add a key press for each character of `keys` to the end of the script.
+    pub fn push_keys(&mut self, keys: &str) {
+        for c in keys.chars() {
+            self.push_key(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+    }
+
+    /// Rows of the screen, without styles and trailing whitespace.
+    pub fn screen(&self) -> Vec<String> {
+        (0..self.buffer.size().1)
+            .map(|row| self.buffer.row_text(row))
+            .collect()
+    }
+
+    /// Get the cell at `(column, row)`.
+    pub fn cell(&self, column: u16, row: u16) -> Option<&Cell> {
+        self.buffer.cell(column, row)
+    }
+
+    /// Number of cells that the last [`Backend::draw`] had to change,
+    /// after scrolling the screen if it moved.
+    pub fn drawn_cells(&self) -> usize {
+        self.drawn_cells
+    }
+
+    /// Position of the cursor, if it is shown.
+    pub fn cursor(&self) -> Option<(u16, u16)> {
+        self.cursor
+    }
+
+    /// [`TestBackend::screen`] at each [`Backend::flush`].
+    pub fn frames(&self) -> &[Vec<String>] {
+        &self.frames
+    }
+}
+
+impl Backend for TestBackend {
+    fn size(&self) -> io::Result<(u16, u16)> {
+        Ok(self.buffer.size())
+    }
+
+    fn poll(&mut self, _timeout: Duration) -> io::Result<bool> {
+        match self.events.is_empty() {
+            true => Err(out_of_events()),
+            false => Ok(true),
+        }
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        let event = self.events.pop_front().ok_or_else(out_of_events)?;
+        if let Event::Resize(width, height) = event {
+            self.buffer = Buffer::new(width, height);
+        }
+        Ok(event)
+    }
+
+    fn draw(&mut self, buffer: &Buffer, scroll_region: Range<u16>) -> io::Result<()> {
+        if buffer.size() != self.buffer.size() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "buffer size doesn't match the screen",
+            ));
+        }
+        if let Some(amount) = buffer.scroll_from(&self.buffer, scroll_region.clone()) {
+            self.buffer.scroll(scroll_region, amount);
+        }
+        self.drawn_cells = buffer.diff(&self.buffer).count();
+        self.buffer = buffer.clone();
+        Ok(())
+    }
+
+    fn set_cursor(&mut self, position: Option<(u16, u16)>) -> io::Result<()> {
+        self.cursor = position;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.frames.push(self.screen());
+        Ok(())
+    }
+}
+
+fn out_of_events() -> io::Error {
+    io::Error::new(io::ErrorKind::UnexpectedEof, "no more scripted events")
+}
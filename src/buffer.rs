@@ -0,0 +1,278 @@
+use std::ops::Range;
+
+use crossterm::style::ContentStyle;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{ansi, text, Span};
+
+/// A column on a [`Buffer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cell {
+    /// The grapheme shown in the column.
+    ///
+    /// Empty if the column is covered by a wide grapheme on its left.
+    pub symbol: String,
+    /// Style of the character.
+    pub style: ContentStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            symbol: " ".to_string(),
+            style: ContentStyle::new(),
+        }
+    }
+}
+
+/// Grid of [`Cell`]s that is drawn on a [`Backend`](crate::Backend).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Buffer {
+    size: (u16, u16),
+    cells: Vec<Cell>,
+}
+
+impl Buffer {
+    /// Create an empty [`Buffer`] of `width` and `height`.
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            size: (width, height),
+            cells: vec![Cell::default(); width as usize * height as usize],
+        }
+    }
+
+    /// Size of the buffer.
+    ///
+    /// `(width, height)`
+    pub fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    /// Get the cell at `(column, row)`.
+    pub fn cell(&self, column: u16, row: u16) -> Option<&Cell> {
+        if column >= self.size.0 {
+            return None;
+        }
+        self.cells
+            .get(row as usize * self.size.0 as usize + column as usize)
+    }
+
+    /// Cells of `row`.
+    pub fn row(&self, row: u16) -> &[Cell] {
+        let width = self.size.0 as usize;
+        let start = (row as usize * width).min(self.cells.len());
+        &self.cells[start..(start + width).min(self.cells.len())]
+    }
+
+    /// Text of `row`, without styles and trailing whitespace.
+    pub fn row_text(&self, row: u16) -> String {
+        let text = self
+            .row(row)
+            .iter()
+            .map(|cell| cell.symbol.as_str())
+            .collect::<String>();
+        text.trim_end().to_string()
+    }
+
+    /// Reset the cells of `row`.
+    pub fn clear_row(&mut self, row: u16) {
+        let width = self.size.0 as usize;
+        let start = (row as usize * width).min(self.cells.len());
+        let end = (start + width).min(self.cells.len());
+        self.cells[start..end].fill(Cell::default());
+    }
+
+    /// Print `text` from the start of `row`, each line of `text` goes to the next row.
+    ///
+    /// `text` can contain SGR escape sequences, characters out of the buffer are dropped.
+    /// Wide characters take two cells, the second one is left empty.
+    pub fn print_at(&mut self, row: u16, text: &str) {
+        self.print_spans(row, &ansi::parse(text));
+    }
+
+    /// Print styled `spans` from the start of `row`, like [`Buffer::print_at`].
+    ///
+    /// Escape sequences in the spans are printed as they are, not parsed.
+    pub fn print_spans(&mut self, row: u16, spans: &[Span]) {
+        let (mut column, mut row) = (0u16, row);
+        let mut last: Option<usize> = None;
+        for span in spans {
+            for symbol in span.text.graphemes(true) {
+                if symbol == "\n" || symbol == "\r\n" {
+                    (column, row) = (0, row.saturating_add(1));
+                    last = None;
+                    continue;
+                }
+                let width = text::width(symbol) as u16;
+                if width == 0 {
+                    // Zero width characters are joined to the previous grapheme.
+                    if let Some(index) = last {
+                        self.cells[index].symbol += symbol;
+                    }
+                    continue;
+                }
+                last = None;
+                if self.index(column.saturating_add(width - 1), row).is_some() {
+                    self.set_cell(column, row, symbol.to_string(), span.style);
+                    for column in column + 1..column + width {
+                        self.set_cell(column, row, String::new(), span.style);
+                    }
+                    last = self.index(column, row);
+                }
+                column = column.saturating_add(width);
+            }
+        }
+    }
+
+    fn index(&self, column: u16, row: u16) -> Option<usize> {
+        (column < self.size.0 && row < self.size.1)
+            .then(|| row as usize * self.size.0 as usize + column as usize)
+    }
+
+    /// Replace the cell at `(column, row)`, clearing the rest of the wide graphemes it covers.
+    fn set_cell(&mut self, column: u16, row: u16, symbol: String, style: ContentStyle) {
+        let Some(index) = self.index(column, row) else {
+            return;
+        };
+        // Cells on the right that were covered by the old grapheme.
+        let mut next = index + 1;
+        while next < self.cells.len()
+            && next % self.size.0 as usize != 0
+            && self.cells[next].symbol.is_empty()
+        {
+            self.cells[next] = Cell::default();
+            next += 1;
+        }
+        // The start of the grapheme that covered the cell.
+        if self.cells[index].symbol.is_empty() {
+            let mut previous = index;
+            while previous % self.size.0 as usize != 0 {
+                previous -= 1;
+                let empty = self.cells[previous].symbol.is_empty();
+                self.cells[previous] = Cell::default();
+                if !empty {
+                    break;
+                }
+            }
+        }
+        self.cells[index] = Cell { symbol, style };
+    }
+
+    /// Move the rows in `region` up by `amount` rows, or down if it is negative.
+    ///
+    /// Rows that are moved in are empty.
+    pub fn scroll(&mut self, region: Range<u16>, amount: i32) {
+        let width = self.size.0 as usize;
+        let region = region.start.min(self.size.1) as usize..region.end.min(self.size.1) as usize;
+        let cells = &mut self.cells[region.start * width..region.end * width];
+        let shift = (amount.unsigned_abs() as usize * width).min(cells.len());
+        if amount > 0 {
+            cells.rotate_left(shift);
+            let len = cells.len();
+            cells[len - shift..].fill(Cell::default());
+        } else {
+            cells.rotate_right(shift);
+            cells[..shift].fill(Cell::default());
+        }
+    }
+
+    /// Find how many rows of `region` moved up (positive) or down (negative) from `previous`,
+    /// if scrolling would leave less rows to redraw.
+    pub(crate) fn scroll_from(&self, previous: &Buffer, region: Range<u16>) -> Option<i32> {
+        if self.size != previous.size || region.end > self.size.1 {
+            return None;
+        }
+        let matching = |amount: i32| {
+            region
+                .clone()
+                .filter(|&row| {
+                    let from = row as i32 + amount;
+                    from >= region.start as i32
+                        && from < region.end as i32
+                        && self.row(row) == previous.row(from as u16)
+                })
+                .count()
+        };
+        let unchanged = matching(0);
+        let max = region.len() as i32 / 2;
+        (-max..=max)
+            .filter(|&amount| amount != 0)
+            .map(|amount| (matching(amount), amount))
+            .filter(|&(count, _)| count > unchanged)
+            .max_by_key(|&(count, amount)| (count, -amount.abs()))
+            .map(|(_, amount)| amount)
+    }
+
+    /// Cells that are different from `previous`, as `(column, row, cell)`.
+    pub(crate) fn diff<'a>(
+        &'a self,
+        previous: &'a Buffer,
+    ) -> impl Iterator<Item = (u16, u16, &'a Cell)> + 'a {
+        let width = self.size.0.max(1) as usize;
+        self.cells
+            .iter()
+            .zip(&previous.cells)
+            .enumerate()
+            .filter(|(_, (cell, old))| cell != old)
+            .map(move |(i, (cell, _))| ((i % width) as u16, (i / width) as u16, cell))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(lines: &[&str]) -> Buffer {
+        let mut buffer = Buffer::new(4, lines.len() as u16);
+        for (row, line) in lines.iter().enumerate() {
+            buffer.print_at(row as u16, line);
+        }
+        buffer
+    }
+
+    #[test]
+    fn scroll_from_finds_moved_rows() {
+        let previous = lines(&["a", "b", "c", "d", "T"]);
+        assert_eq!(
+            lines(&["b", "c", "d", "e", "T"]).scroll_from(&previous, 0..4),
+            Some(1)
+        );
+        assert_eq!(
+            lines(&["z", "a", "b", "c", "T"]).scroll_from(&previous, 0..4),
+            Some(-1)
+        );
+        assert_eq!(
+            lines(&["c", "d", "e", "f", "T"]).scroll_from(&previous, 0..4),
+            Some(2)
+        );
+        assert_eq!(previous.scroll_from(&previous, 0..4), None);
+        assert_eq!(
+            lines(&["w", "x", "y", "z", "T"]).scroll_from(&previous, 0..4),
+            None
+        );
+    }
+
+    #[test]
+    fn scroll_from_stays_in_the_region() {
+        let previous = lines(&["a", "b", "c", "d", "T"]);
+        let current = lines(&["b", "c", "d", "T", "U"]);
+        // The status bar moving into the content is not scrolled from below the region.
+        let mut scrolled = previous.clone();
+        scrolled.scroll(0..4, current.scroll_from(&previous, 0..4).unwrap());
+        assert_eq!(scrolled.row_text(3), "");
+        assert_eq!(scrolled.row_text(4), "T");
+        assert_eq!(current.scroll_from(&previous, 0..6), None);
+    }
+
+    #[test]
+    fn diff_has_changed_cells() {
+        let previous = lines(&["ab", "cd"]);
+        let current = lines(&["ab", "ce"]);
+        let diff: Vec<_> = current
+            .diff(&previous)
+            .map(|(column, row, cell)| (column, row, cell.symbol.as_str()))
+            .collect();
+        assert_eq!(diff, [(1, 1, "e")]);
+        assert_eq!(current.diff(&current).count(), 0);
+    }
+}
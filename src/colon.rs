@@ -0,0 +1,344 @@
+use std::{fs, str::FromStr};
+
+use crate::{Command, CommandType, State};
+
+/// Result of a [`Command`](crate::Command).
+///
+/// `Ok` holds whether the screen should be redrawn,
+/// `Err` holds a message that is shown in the [`StatusBar`](crate::StatusBar).
+pub type CommandResult = Result<bool, String>;
+
+/// Arguments given to a [`Command`](crate::Command).
+///
+/// For `:goto 120` the arguments are `["120"]`.
+/// Arguments are separated by whitespace, quotes (`"a b"`, `'a b'`) and `\` can be used to
+/// include whitespace in an argument. Like in a shell, text in single quotes is taken as it is,
+/// without `\` escapes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommandArgs {
+    /// Parsed arguments.
+    pub args: Vec<String>,
+    /// Number typed before the key binding, like `5` in `5j`.
+    ///
+    /// Always `None` for colon commands.
+    pub count: Option<usize>,
+}
+
+impl CommandArgs {
+    /// Number of arguments.
+    pub fn len(&self) -> usize {
+        self.args.len()
+    }
+
+    /// Returns true if there are no arguments.
+    pub fn is_empty(&self) -> bool {
+        self.args.is_empty()
+    }
+
+    /// Get the argument at `index`.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.args.get(index).map(String::as_str)
+    }
+
+    /// Parse the argument at `index`.
+    ///
+    /// Returns an error message if the argument is missing or invalid.
+    pub fn parse<T: FromStr>(&self, index: usize) -> Result<T, String> {
+        let arg = self
+            .get(index)
+            .ok_or_else(|| format!("missing argument {}", index + 1))?;
+        arg.parse()
+            .map_err(|_| format!("invalid argument '{}'", arg))
+    }
+
+    /// Returns an error message if the number of arguments is not in `min..=max`.
+    pub fn expect_len(&self, min: usize, max: usize) -> Result<(), String> {
+        if self.len() < min {
+            Err(format!("missing argument {}", self.len() + 1))
+        } else if self.len() > max {
+            Err(match max {
+                0 => "takes no arguments".to_string(),
+                max => format!("takes at most {} arguments", max),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Split a colon command input into its name and arguments.
+pub fn parse_colon(input: &str) -> Result<(String, CommandArgs), String> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', None | Some('"')) => {
+                let escaped = chars.next().ok_or("trailing '\\'")?;
+                word.get_or_insert_with(String::new).push(escaped);
+            }
+            (c, Some(q)) if c == q => quote = None,
+            (c, Some(_)) => word.get_or_insert_with(String::new).push(c),
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (c, None) if c.is_whitespace() => words.extend(word.take()),
+            (c, None) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if let Some(q) = quote {
+        return Err(format!("unterminated {}", q));
+    }
+    words.extend(word);
+
+    let mut words = words.into_iter();
+    let name = words.next().unwrap_or_default();
+    Ok((
+        name,
+        CommandArgs {
+            args: words.collect(),
+            count: None,
+        },
+    ))
+}
+
+impl<'a> State<'a> {
+    /// Find the command of a colon command name.
+    ///
+    /// `name` can be any of the [`CommandType::Colon`] names of a command,
+    /// or a prefix of them if the prefix is unique, like `q` for `quit`.
+    ///
+    /// Returns the full name and the command, or an error message.
+    pub fn find_colon(&self, name: &str) -> Result<(String, Command), String> {
+        let colon_names = |exact: bool| {
+            self.commands
+                .0
+                .iter()
+                .filter_map(|command| {
+                    command.cmd.iter().find_map(|cmd_type| match cmd_type {
+                        CommandType::Colon(colon)
+                            if (exact && colon == name) || (!exact && colon.starts_with(name)) =>
+                        {
+                            Some((colon.clone(), command.clone()))
+                        }
+                        _ => None,
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut found = colon_names(true);
+        if found.is_empty() {
+            found = colon_names(false);
+        } else {
+            found.truncate(1);
+        }
+        match found.len() {
+            0 => Err(format!("Unknown command: {}", name)),
+            1 => Ok(found.remove(0)),
+            _ => {
+                let names = found
+                    .into_iter()
+                    .map(|(colon, _)| colon)
+                    .collect::<Vec<_>>();
+                Err(format!(
+                    "Ambiguous command: {} ({})",
+                    name,
+                    names.join(", ")
+                ))
+            }
+        }
+    }
+
+    /// Parse and execute a colon command, e.g. `"goto 120"`.
+    ///
+    /// A line number alone, e.g. `"120"`, runs `goto` with it.
+    ///
+    /// See: [`State::find_colon`]
+    ///
+    /// Errors are shown in the [`StatusBar`](crate::StatusBar).
+    /// Returns true if the screen should be redrawn.
+    pub fn execute_colon(&mut self, input: &str) -> bool {
+        let result = parse_colon(input).and_then(|(name, args)| {
+            if name.is_empty() {
+                return Ok(false);
+            }
+            let (name, args) = match name.parse::<usize>() {
+                Ok(_) if args.is_empty() => (
+                    "goto".to_string(),
+                    CommandArgs {
+                        args: vec![name],
+                        count: None,
+                    },
+                ),
+                _ => (name, args),
+            };
+            let (colon, command) = self.find_colon(&name)?;
+            command
+                .execute(self, &args)
+                .map_err(|err| format!("{}: {}", colon, err))
+        });
+        result.unwrap_or_else(|err| {
+            self.message = Some(err);
+            true
+        })
+    }
+
+    /// Get completion candidates for a colon command input.
+    ///
+    /// The first word is completed with colon command names,
+    /// the others with the [`Command::completer`] of the command.
+    ///
+    /// Returns the char index where the completed word starts, and the candidates.
+    pub fn complete_colon(&self, input: &str) -> (usize, Vec<String>) {
+        let word_start = input
+            .char_indices()
+            .rfind(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &input[word_start..];
+        let start = input[..word_start].chars().count();
+        let mut words = input[..word_start].split_whitespace();
+
+        let Some(name) = words.next() else {
+            let mut names = self
+                .commands
+                .0
+                .iter()
+                .flat_map(|command| command.cmd.iter())
+                .filter_map(|cmd_type| match cmd_type {
+                    CommandType::Colon(colon) if colon.starts_with(word) => Some(colon.clone()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            names.sort();
+            names.dedup();
+            return (start, names);
+        };
+
+        let candidates = match self.find_colon(name) {
+            Ok((
+                _,
+                Command {
+                    completer: Some(completer),
+                    ..
+                },
+            )) => completer(self, words.count(), word),
+            _ => vec![],
+        };
+        (start, candidates)
+    }
+}
+
+/// Complete `partial` with the options that start with it.
+///
+/// Can be used in a [`Command::completer`].
+pub fn complete_options(options: &[&str], partial: &str) -> Vec<String> {
+    options
+        .iter()
+        .filter(|option| option.starts_with(partial))
+        .map(|option| option.to_string())
+        .collect()
+}
+
+/// Complete `partial` with paths of files and directories.
+///
+/// Directories end with `/`. Hidden files are only completed if `partial` names them.
+/// Can be used in a [`Command::completer`].
+pub fn complete_path(partial: &str) -> Vec<String> {
+    let (dir, prefix) = match partial.rfind('/') {
+        Some(i) => partial.split_at(i + 1),
+        None => ("", partial),
+    };
+    let Ok(entries) = fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return vec![];
+    };
+    let mut paths = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir, name, slash))
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Vec<String>, String> {
+        let (name, args) = parse_colon(input)?;
+        Ok(std::iter::once(name).chain(args.args).collect())
+    }
+
+    #[test]
+    fn words_are_split_on_whitespace() {
+        assert_eq!(parse("goto  120\t").unwrap(), ["goto", "120"]);
+        assert_eq!(parse("").unwrap(), [""]);
+        assert_eq!(parse("   ").unwrap(), [""]);
+    }
+
+    #[test]
+    fn quotes() {
+        assert_eq!(parse(r#"e "a b" 'c d'"#).unwrap(), ["e", "a b", "c d"]);
+        assert_eq!(parse(r#"e a"b c"d"#).unwrap(), ["e", "ab cd"]);
+        assert_eq!(
+            parse(r#"e "it's" 'say "hi"'"#).unwrap(),
+            ["e", "it's", "say \"hi\""]
+        );
+        assert_eq!(parse("e '' \"\"").unwrap(), ["e", "", ""]);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(parse(r"e a\ b \'c").unwrap(), ["e", "a b", "'c"]);
+        assert_eq!(parse(r#"e "a\"b\\c""#).unwrap(), ["e", r#"a"b\c"#]);
+    }
+
+    #[test]
+    fn single_quoted_text_is_literal() {
+        assert_eq!(parse(r"e 'C:\tmp\x'").unwrap(), ["e", r"C:\tmp\x"]);
+        assert_eq!(parse(r"e 'a\'").unwrap(), ["e", r"a\"]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse("e 'a").unwrap_err(), "unterminated '");
+        assert_eq!(parse("e \"a").unwrap_err(), "unterminated \"");
+        assert_eq!(parse(r"e a\").unwrap_err(), "trailing '\\'");
+    }
+
+    #[test]
+    fn parse_arguments() {
+        let args = parse_colon("set 5 x").unwrap().1;
+        assert_eq!(args.parse::<usize>(0), Ok(5));
+        assert_eq!(
+            args.parse::<usize>(1),
+            Err("invalid argument 'x'".to_string())
+        );
+        assert_eq!(
+            args.parse::<usize>(2),
+            Err("missing argument 3".to_string())
+        );
+    }
+
+    #[test]
+    fn expect_len() {
+        let args = parse_colon("set a b").unwrap().1;
+        assert_eq!(args.expect_len(1, 2), Ok(()));
+        assert_eq!(args.expect_len(3, 3), Err("missing argument 3".to_string()));
+        assert_eq!(
+            args.expect_len(0, 1),
+            Err("takes at most 1 arguments".to_string())
+        );
+        assert_eq!(args.expect_len(0, 0), Err("takes no arguments".to_string()));
+    }
+}
@@ -0,0 +1,375 @@
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+    sync::{
+        mpsc::{channel, Receiver, TryRecvError},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use crate::{ansi, Span};
+
+/// How long a following [`ReaderSource`] waits before reading again after reaching the end.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Source of the lines shown by a [`State`](crate::State).
+///
+/// Sources may still be producing lines while the pager is running,
+/// [`ContentSource::poll`] is called by [`run`](crate::run) to pull the new ones.
+pub trait ContentSource {
+    /// Pull the lines that became available since the last call, without blocking.
+    ///
+    /// Returns true if any line was added.
+    fn poll(&mut self) -> bool {
+        false
+    }
+
+    /// Returns true if the source will not produce any more lines.
+    fn is_complete(&self) -> bool {
+        true
+    }
+
+    /// Fraction of the content that is read so far, from `0.0` to `1.0`, if it is known.
+    fn progress(&self) -> Option<f32> {
+        None
+    }
+
+    /// Number of lines available.
+    fn line_count(&self) -> usize;
+
+    /// Get the line at `index`, without the line ending.
+    fn line(&self, index: usize) -> Option<Cow<'_, str>>;
+
+    /// Iterate over the available lines, starting from `start`.
+    fn lines_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        Box::new((start..self.line_count()).map_while(|index| self.line(index)))
+    }
+
+    /// Iterate over the styled spans of the available lines, starting from `start`.
+    ///
+    /// By default the lines are parsed for SGR escape sequences.
+    fn spans_from(&self, start: usize) -> Box<dyn Iterator<Item = Vec<Span>> + '_> {
+        Box::new(self.lines_from(start).map(|line| ansi::parse(&line)))
+    }
+
+    /// Iterate over the text of the available lines without styles, starting from `start`.
+    ///
+    /// Used to search, filter and wrap the lines.
+    fn texts_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        Box::new(self.lines_from(start).map(|line| match line {
+            Cow::Borrowed(line) => ansi::strip(line),
+            Cow::Owned(line) => Cow::Owned(ansi::strip(&line).into_owned()),
+        }))
+    }
+
+    /// Index of the line that contains the byte at `offset` of the content.
+    ///
+    /// Returns `None` if the offset is after the available lines.
+    /// By default the lines are counted with a one byte line ending.
+    fn line_at_byte(&self, offset: usize) -> Option<usize> {
+        let mut end = 0;
+        for (index, line) in self.lines_from(0).enumerate() {
+            end += line.len() + 1;
+            if offset < end {
+                return Some(index);
+            }
+        }
+        None
+    }
+}
+
+/// Conversion into a boxed [`ContentSource`], used by [`State::new`](crate::State::new).
+///
+/// A [`String`] becomes a [`TextSource`].
+pub trait IntoContentSource {
+    /// Box `self` as a [`ContentSource`].
+    fn into_content_source(self) -> Box<dyn ContentSource>;
+}
+
+impl<C: ContentSource + 'static> IntoContentSource for C {
+    fn into_content_source(self) -> Box<dyn ContentSource> {
+        Box::new(self)
+    }
+}
+
+impl IntoContentSource for String {
+    fn into_content_source(self) -> Box<dyn ContentSource> {
+        Box::new(TextSource::new(self))
+    }
+}
+
+impl IntoContentSource for &str {
+    fn into_content_source(self) -> Box<dyn ContentSource> {
+        Box::new(TextSource::new(self.to_string()))
+    }
+}
+
+/// Lines stored in one [`String`], with the offsets where they start.
+#[derive(Clone, Debug, Default)]
+struct LineBuffer {
+    text: String,
+    starts: Vec<usize>,
+}
+
+impl LineBuffer {
+    /// Index the lines of `text`, like [`str::lines`].
+    fn new(text: String) -> Self {
+        let mut starts = vec![];
+        if !text.is_empty() {
+            starts.push(0);
+        }
+        starts.extend(
+            text.match_indices('\n')
+                .map(|(i, _)| i + 1)
+                .filter(|&start| start < text.len()),
+        );
+        Self { text, starts }
+    }
+
+    /// Add a line without a line ending.
+    fn push(&mut self, line: &str) {
+        if !self.starts.is_empty() {
+            self.text.push('\n');
+        }
+        self.starts.push(self.text.len());
+        self.text.push_str(line);
+    }
+
+    fn len(&self) -> usize {
+        self.starts.len()
+    }
+
+    fn line_at_byte(&self, offset: usize) -> Option<usize> {
+        if offset >= self.text.len() {
+            return None;
+        }
+        Some(self.starts.partition_point(|&start| start <= offset) - 1)
+    }
+
+    fn get(&self, index: usize) -> Option<&str> {
+        let start = *self.starts.get(index)?;
+        let end = self
+            .starts
+            .get(index + 1)
+            .map_or(self.text.len(), |next| next - 1);
+        let line = &self.text[start..end];
+        let line = line.strip_suffix('\n').unwrap_or(line);
+        Some(line.strip_suffix('\r').unwrap_or(line))
+    }
+}
+
+/// [`ContentSource`] of a text that is already in memory.
+///
+/// The lines are indexed once, so getting a line does not scan the text.
+#[derive(Clone, Debug, Default)]
+pub struct TextSource {
+    lines: LineBuffer,
+}
+
+impl TextSource {
+    /// Create a [`TextSource`] of `text`.
+    pub fn new(text: String) -> Self {
+        Self {
+            lines: LineBuffer::new(text),
+        }
+    }
+}
+
+impl From<String> for TextSource {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
+/// Create a [`TextSource`] of lines, newlines in the items start new lines too.
+impl<S: AsRef<str>> FromIterator<S> for TextSource {
+    fn from_iter<I: IntoIterator<Item = S>>(lines: I) -> Self {
+        let mut buffer = LineBuffer::default();
+        for line in lines {
+            for line in line.as_ref().split('\n') {
+                buffer.push(line);
+            }
+        }
+        Self { lines: buffer }
+    }
+}
+
+impl ContentSource for TextSource {
+    fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    fn line(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.lines.get(index).map(Cow::Borrowed)
+    }
+
+    fn line_at_byte(&self, offset: usize) -> Option<usize> {
+        self.lines.line_at_byte(offset)
+    }
+}
+
+/// [`ContentSource`] that receives text from a channel.
+///
+/// Each message is one line, messages containing newlines are split into multiple lines.
+/// The source is complete when all senders are dropped.
+pub struct ChannelSource {
+    lines: LineBuffer,
+    receiver: Receiver<String>,
+    complete: bool,
+}
+
+impl ChannelSource {
+    /// Create a [`ChannelSource`] that receives lines from `receiver`.
+    pub fn new(receiver: Receiver<String>) -> Self {
+        Self {
+            lines: LineBuffer::default(),
+            receiver,
+            complete: false,
+        }
+    }
+}
+
+impl ContentSource for ChannelSource {
+    fn poll(&mut self) -> bool {
+        let line_count = self.lines.len();
+        while !self.complete {
+            match self.receiver.try_recv() {
+                Ok(text) => {
+                    for line in text.strip_suffix('\n').unwrap_or(&text).split('\n') {
+                        self.lines.push(line.strip_suffix('\r').unwrap_or(line));
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.complete = true,
+            }
+        }
+        self.lines.len() != line_count
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    fn line(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.lines.get(index).map(Cow::Borrowed)
+    }
+
+    fn line_at_byte(&self, offset: usize) -> Option<usize> {
+        self.lines.line_at_byte(offset)
+    }
+}
+
+/// [`ContentSource`] that reads lines from a [`Read`] stream, like a file or a pipe.
+///
+/// The stream is read on a background thread, so the pager does not wait for slow producers.
+/// Invalid UTF-8 is replaced with `U+FFFD`.
+pub struct ReaderSource {
+    inner: ChannelSource,
+    error: Arc<Mutex<Option<io::Error>>>,
+}
+
+impl ReaderSource {
+    /// Start reading lines from `reader`.
+    pub fn new<R: Read + Send + 'static>(reader: R) -> Self {
+        Self::spawn(reader, false)
+    }
+
+    /// Start reading lines from `reader`, and keep reading after the end is reached.
+    ///
+    /// Use it for streams that grow, like a log file that is still written.
+    /// An unfinished last line is held back until its line ending arrives.
+    pub fn follow<R: Read + Send + 'static>(reader: R) -> Self {
+        Self::spawn(reader, true)
+    }
+
+    fn spawn<R: Read + Send + 'static>(reader: R, follow: bool) -> Self {
+        let (sender, receiver) = channel();
+        let error = Arc::new(Mutex::new(None));
+        let thread_error = Arc::clone(&error);
+        thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut buf = vec![];
+            loop {
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) if follow => thread::sleep(FOLLOW_INTERVAL),
+                    Ok(0) if buf.is_empty() => break,
+                    Ok(_) if follow && !buf.ends_with(b"\n") => {}
+                    Ok(_) => {
+                        let line = String::from_utf8_lossy(&buf);
+                        let line = line.strip_suffix('\n').unwrap_or(&line);
+                        let line = line.strip_suffix('\r').unwrap_or(line);
+                        if sender.send(line.to_string()).is_err() {
+                            break;
+                        }
+                        buf.clear();
+                    }
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(err) => {
+                        *thread_error.lock().unwrap() = Some(err);
+                        break;
+                    }
+                }
+            }
+        });
+        Self {
+            inner: ChannelSource::new(receiver),
+            error,
+        }
+    }
+
+    /// Open the file at `path` and start reading lines from it.
+    pub fn file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(File::open(path)?))
+    }
+
+    /// Open the file at `path` and follow it as it grows.
+    ///
+    /// See: [`ReaderSource::follow`]
+    pub fn follow_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::follow(File::open(path)?))
+    }
+
+    /// Start reading lines from the standard input.
+    pub fn stdin() -> Self {
+        Self::new(io::stdin())
+    }
+
+    /// Take the error that stopped reading, if there is any.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.lock().unwrap().take()
+    }
+}
+
+impl ContentSource for ReaderSource {
+    fn poll(&mut self) -> bool {
+        self.inner.poll()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.inner.is_complete()
+    }
+
+    fn line_count(&self) -> usize {
+        self.inner.line_count()
+    }
+
+    fn line(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.inner.line(index)
+    }
+
+    fn lines_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        self.inner.lines_from(start)
+    }
+
+    fn line_at_byte(&self, offset: usize) -> Option<usize> {
+        self.inner.line_at_byte(offset)
+    }
+}
@@ -0,0 +1,372 @@
+use std::borrow::Cow;
+
+use crossterm::event::KeyCode;
+use regex::Regex;
+
+use crate::{prompt, Command, CommandList, CommandType, ContentSource, Search, Span, State};
+
+/// Lines that are checked at once, the rest are checked in chunks while the pager is idle.
+const FILTER_CHUNK: usize = 10_000;
+
+/// Lines of the content that a [`State`] shows while it is filtered.
+///
+/// See: [`State::set_filter`]
+pub struct Filter {
+    /// Returns true for lines to show, called with lines without escape sequences.
+    predicate: Box<dyn Fn(&str) -> bool>,
+
+    /// Indices of the matching lines in the content.
+    lines: Vec<usize>,
+
+    /// Number of content lines that are checked.
+    checked_lines: usize,
+
+    /// True if every available line is checked.
+    checked: bool,
+
+    /// Content line at the top of the view when the filter was set, the view moves to it once
+    /// it is checked unless it is moved before.
+    pub(crate) top: Option<usize>,
+}
+
+impl Filter {
+    /// Show the lines that match `pattern`, or the ones that don't if `invert` is true.
+    pub fn new(pattern: &str, invert: bool) -> Result<Self, regex::Error> {
+        let regex = Regex::new(pattern)?;
+        Ok(Self::with_predicate(move |line| {
+            regex.is_match(line) != invert
+        }))
+    }
+
+    /// Show the lines that `predicate` returns true for.
+    ///
+    /// Lines are given without escape sequences.
+    pub fn with_predicate<F: Fn(&str) -> bool + 'static>(predicate: F) -> Self {
+        Self {
+            predicate: Box::new(predicate),
+            lines: vec![],
+            checked_lines: 0,
+            checked: false,
+            top: None,
+        }
+    }
+
+    /// Returns true if every available line of the content is checked.
+    pub fn is_checked(&self) -> bool {
+        self.checked
+    }
+
+    /// Number of lines that are shown, see [`Filter::is_checked`].
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Index in the content of the shown line at `index`.
+    pub fn content_index(&self, index: usize) -> Option<usize> {
+        self.lines.get(index).copied()
+    }
+
+    /// Content indices of the shown lines from `start`.
+    fn shown_from(&self, start: usize) -> &[usize] {
+        &self.lines[start.min(self.lines.len())..]
+    }
+
+    /// Index of the first shown line at or after the content line at `content_index`.
+    pub(crate) fn shown_index(&self, content_index: usize) -> usize {
+        self.lines.partition_point(|&index| index < content_index)
+    }
+
+    /// Returns true if the content line at `content_index` is checked.
+    fn has_checked(&self, content_index: usize) -> bool {
+        self.checked || content_index < self.checked_lines
+    }
+
+    /// Check the next chunk of lines of `content` that are not checked yet.
+    ///
+    /// Returns true if any line is checked.
+    pub(crate) fn update(&mut self, content: &dyn ContentSource) -> bool {
+        let start = self.checked_lines;
+        for (index, line) in content.texts_from(start).take(FILTER_CHUNK).enumerate() {
+            if (self.predicate)(&line) {
+                self.lines.push(start + index);
+            }
+            self.checked_lines = start + index + 1;
+        }
+        self.checked = self.checked_lines >= content.line_count();
+        self.checked_lines > start
+    }
+}
+
+/// Lines of a content that a [`State`] shows, the ones that match its [`Filter`] if it has one.
+pub(crate) struct ContentView<'s> {
+    content: &'s dyn ContentSource,
+    filter: Option<&'s Filter>,
+}
+
+impl<'s> ContentView<'s> {
+    pub fn new(content: &'s dyn ContentSource, filter: Option<&'s Filter>) -> Self {
+        Self { content, filter }
+    }
+}
+
+impl ContentSource for ContentView<'_> {
+    fn is_complete(&self) -> bool {
+        self.content.is_complete() && self.filter.is_none_or(Filter::is_checked)
+    }
+
+    fn progress(&self) -> Option<f32> {
+        self.content.progress()
+    }
+
+    fn line_count(&self) -> usize {
+        match self.filter {
+            Some(filter) => filter.line_count(),
+            None => self.content.line_count(),
+        }
+    }
+
+    fn line(&self, index: usize) -> Option<Cow<'_, str>> {
+        match self.filter {
+            Some(filter) => self.content.line(filter.content_index(index)?),
+            None => self.content.line(index),
+        }
+    }
+
+    fn lines_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        match self.filter {
+            Some(filter) => walk(filter.shown_from(start), |index| {
+                self.content.lines_from(index)
+            }),
+            None => self.content.lines_from(start),
+        }
+    }
+
+    fn spans_from(&self, start: usize) -> Box<dyn Iterator<Item = Vec<Span>> + '_> {
+        match self.filter {
+            Some(filter) => walk(filter.shown_from(start), |index| {
+                self.content.spans_from(index)
+            }),
+            None => self.content.spans_from(start),
+        }
+    }
+
+    fn texts_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        match self.filter {
+            Some(filter) => walk(filter.shown_from(start), |index| {
+                self.content.texts_from(index)
+            }),
+            None => self.content.texts_from(start),
+        }
+    }
+}
+
+/// Items of the content lines at `indices`, taken from the iterators that `from` starts at a
+/// content line.
+///
+/// Consecutive lines are taken from the same iterator, so a source that starts its iterators
+/// from an earlier line, e.g. to highlight it, doesn't start again for each line.
+fn walk<'a, T: 'a>(
+    indices: &'a [usize],
+    from: impl Fn(usize) -> Box<dyn Iterator<Item = T> + 'a> + 'a,
+) -> Box<dyn Iterator<Item = T> + 'a> {
+    // The iterator and the index of its next line.
+    let mut current: Option<(usize, Box<dyn Iterator<Item = T> + 'a>)> = None;
+    Box::new(indices.iter().map_while(move |&index| {
+        if !matches!(&current, Some((next, _)) if *next == index) {
+            current = Some((index, from(index)));
+        }
+        let (next, lines) = current.as_mut()?;
+        *next += 1;
+        lines.next()
+    }))
+}
+
+impl<'a> State<'a> {
+    /// Lines that are shown, see [`State::set_filter`].
+    pub(crate) fn view(&self) -> ContentView<'_> {
+        ContentView::new(&*self.content, self.filter.as_ref())
+    }
+
+    /// Index in [`State::content`] of the shown line at `index`.
+    pub(crate) fn content_index(&self, index: usize) -> usize {
+        match &self.filter {
+            Some(filter) => filter.content_index(index).unwrap_or(index),
+            None => index,
+        }
+    }
+
+    /// Index of the shown line at or after the content line at `index`.
+    pub(crate) fn shown_index(&self, index: usize) -> usize {
+        match &self.filter {
+            Some(filter) => filter.shown_index(index),
+            None => index,
+        }
+    }
+
+    /// Show only the lines that match `filter`.
+    ///
+    /// Line numbers still show the position of the lines in the content, and the view stays
+    /// around the line at the top. The first lines are checked at once, the others while the
+    /// pager is idle.
+    pub fn set_filter(&mut self, mut filter: Filter) {
+        let top = self.content_index(self.pos.1);
+        filter.update(&*self.content);
+        if !filter.has_checked(top) {
+            filter.top = Some(top);
+        }
+        let line = filter.shown_index(top);
+        self.filter = Some(filter);
+        self.filter_changed(line);
+    }
+
+    /// Check the next chunk of lines for the current filter.
+    ///
+    /// Returns true if any line is checked.
+    pub(crate) fn check_filter(&mut self) -> bool {
+        let Some(filter) = &mut self.filter else {
+            return false;
+        };
+        if filter.is_checked() || !filter.update(&*self.content) {
+            return false;
+        }
+        if let Some(top) = filter.top.filter(|&top| filter.has_checked(top)) {
+            filter.top = None;
+            let line = filter.shown_index(top);
+            self.scroll_to(line);
+        }
+        if let Some(search) = &mut self.search {
+            search.update(&ContentView::new(&*self.content, self.filter.as_ref()));
+        }
+        if self.following {
+            self.scroll_to_end();
+        }
+        true
+    }
+
+    /// Show all lines again.
+    ///
+    /// Returns false if there is no filter.
+    pub fn clear_filter(&mut self) -> bool {
+        let top = self.content_index(self.pos.1);
+        if self.filter.take().is_none() {
+            return false;
+        }
+        self.filter_changed(top);
+        true
+    }
+
+    /// Get the current filter.
+    pub fn get_filter(&self) -> Option<&Filter> {
+        self.filter.as_ref()
+    }
+
+    /// Move to `line` of the new view, and search it again.
+    fn filter_changed(&mut self, line: usize) {
+        // Rows are kept by the index of the shown line.
+        *self.wrap_cache.borrow_mut() = Default::default();
+        if let Some(search) = &self.search {
+            // Matches are found again, since they are kept by line index.
+            let search = Search::new(search.regex.as_str(), search.direction);
+            self.search = search.ok();
+        }
+        self.scroll_to(line);
+    }
+}
+
+/// Prompt for a pattern and filter the lines with it.
+///
+/// `!pattern` shows the lines that don't match, an empty pattern clears the filter.
+fn filter_command(state: &mut State) -> bool {
+    let pattern = match prompt(state, "&") {
+        Ok(Some(pattern)) => pattern,
+        Ok(None) => return true,
+        Err(err) => {
+            state.message = Some(err.to_string());
+            return true;
+        }
+    };
+    if pattern.is_empty() {
+        state.clear_filter();
+        return true;
+    }
+    let (pattern, invert) = match pattern.strip_prefix('!') {
+        Some(pattern) => (pattern, true),
+        None => (pattern.as_str(), false),
+    };
+    match Filter::new(pattern, invert) {
+        Ok(filter) => state.set_filter(filter),
+        Err(err) => {
+            state.message = Some(
+                err.to_string()
+                    .lines()
+                    .last()
+                    .unwrap_or_default()
+                    .to_string(),
+            )
+        }
+    }
+    true
+}
+
+impl CommandList {
+    /// Default bundle of 'filter' commands.
+    ///
+    /// Includes: `&` key
+    pub fn filter() -> Self {
+        use CommandType::*;
+        Self(vec![Command::new(
+            vec![Key(KeyCode::Char('&'))],
+            "Show only matching lines, `!` inverts, empty shows all".to_string(),
+            filter_command,
+        )])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::TextSource;
+
+    /// Counts the iterators that are started on it.
+    struct Counting {
+        content: TextSource,
+        started: Cell<usize>,
+    }
+
+    impl ContentSource for Counting {
+        fn line_count(&self) -> usize {
+            self.content.line_count()
+        }
+
+        fn line(&self, index: usize) -> Option<Cow<'_, str>> {
+            self.content.line(index)
+        }
+
+        fn texts_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+            self.started.set(self.started.get() + 1);
+            self.content.texts_from(start)
+        }
+    }
+
+    #[test]
+    fn consecutive_lines_are_taken_from_one_iterator() {
+        let content = Counting {
+            content: (0..20).map(|i| i.to_string()).collect(),
+            started: Cell::new(0),
+        };
+        let mut filter = Filter::new("^(1|2|3|7|8|19)$", false).unwrap();
+        filter.update(&content);
+        content.started.set(0);
+
+        let view = ContentView::new(&content, Some(&filter));
+        let lines: Vec<_> = view.texts_from(0).collect();
+        assert_eq!(lines, ["1", "2", "3", "7", "8", "19"]);
+        assert_eq!(content.started.get(), 3);
+
+        let lines: Vec<_> = view.texts_from(4).collect();
+        assert_eq!(lines, ["8", "19"]);
+        assert_eq!(view.texts_from(6).next(), None);
+    }
+}
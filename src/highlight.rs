@@ -0,0 +1,273 @@
+use std::{borrow::Cow, cell::RefCell, path::Path, sync::OnceLock};
+
+use crossterm::style::{Attribute, Color, ContentStyle};
+use syntect::{
+    highlighting::{
+        FontStyle, HighlightIterator, HighlightState, Highlighter, Style, Theme, ThemeSet,
+    },
+    parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet},
+};
+
+use crate::{ContentSource, Span};
+
+/// Theme used by [`HighlightedSource`] unless it is changed.
+const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// Lines between the saved highlighting states, other lines are highlighted from them.
+const CHECKPOINT_INTERVAL: usize = 64;
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme_set() -> &'static ThemeSet {
+    static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+    THEME_SET.get_or_init(ThemeSet::load_defaults)
+}
+
+/// Highlighting state at the start of a line.
+#[derive(Clone)]
+struct Checkpoint {
+    parse: ParseState,
+    highlight: HighlightState,
+}
+
+/// [`ContentSource`] that highlights the lines of another source with a `syntect` syntax.
+///
+/// Highlighted lines are returned as styled spans by [`ContentSource::spans_from`].
+/// [`ContentSource::line`] returns the lines of the other source as they are, so content that
+/// is printed instead of paged, e.g. to a file, is not changed.
+///
+/// Requires the `syntax-highlighting` feature.
+///
+/// ```
+/// # use pager_rs::{ContentSource, HighlightedSource, TextSource};
+/// let content = TextSource::new("fn main() {}".to_string());
+/// let content = HighlightedSource::new(content, "rs");
+/// assert_eq!(content.syntax_name(), "Rust");
+/// ```
+pub struct HighlightedSource<C> {
+    content: C,
+    syntax: &'static SyntaxReference,
+    theme: &'static Theme,
+    /// States at the start of every [`CHECKPOINT_INTERVAL`]th line.
+    checkpoints: RefCell<Vec<Checkpoint>>,
+}
+
+impl<C: ContentSource> HighlightedSource<C> {
+    /// Highlight `content` with the syntax of a file extension or language name, e.g. `"rs"` or
+    /// `"Rust"`.
+    ///
+    /// Content is shown as plain text if there is no such syntax.
+    pub fn new(content: C, syntax: &str) -> Self {
+        let syntax_set = syntax_set();
+        let syntax = syntax_set
+            .find_syntax_by_token(syntax)
+            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+        Self::with_syntax(content, syntax)
+    }
+
+    /// Highlight `content` with the syntax for the extension or name of the file at `path`.
+    ///
+    /// Content is shown as plain text if there is no such syntax.
+    pub fn for_path<P: AsRef<Path>>(content: C, path: P) -> Self {
+        let syntax_set = syntax_set();
+        let path = path.as_ref();
+        let syntax = [path.extension(), path.file_name()]
+            .into_iter()
+            .flatten()
+            .filter_map(|token| syntax_set.find_syntax_by_extension(&token.to_string_lossy()))
+            .next()
+            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+        Self::with_syntax(content, syntax)
+    }
+
+    fn with_syntax(content: C, syntax: &'static SyntaxReference) -> Self {
+        Self {
+            content,
+            syntax,
+            theme: &theme_set().themes[DEFAULT_THEME],
+            checkpoints: RefCell::default(),
+        }
+    }
+
+    /// Name of the syntax that is used, `"Plain Text"` if none is found.
+    pub fn syntax_name(&self) -> &str {
+        &self.syntax.name
+    }
+
+    /// Names of the themes that can be used with [`HighlightedSource::set_theme`].
+    pub fn theme_names() -> Vec<&'static str> {
+        theme_set().themes.keys().map(String::as_str).collect()
+    }
+
+    /// Use the theme named `name`.
+    ///
+    /// Returns false if there is no such theme.
+    pub fn set_theme(&mut self, name: &str) -> bool {
+        match theme_set().themes.get(name) {
+            Some(theme) => {
+                self.theme = theme;
+                self.checkpoints.get_mut().clear();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Highlighting state at the start of the line at `index`.
+    fn state_at(&self, index: usize) -> Checkpoint {
+        let highlighter = Highlighter::new(self.theme);
+        let mut checkpoints = self.checkpoints.borrow_mut();
+        // Lines are only appended, less lines means the content is replaced.
+        if checkpoints.len() > self.content.line_count() / CHECKPOINT_INTERVAL + 1 {
+            checkpoints.clear();
+        }
+        if checkpoints.is_empty() {
+            checkpoints.push(Checkpoint {
+                parse: ParseState::new(self.syntax),
+                highlight: HighlightState::new(&highlighter, ScopeStack::new()),
+            });
+        }
+        let wanted = index / CHECKPOINT_INTERVAL;
+        let saved = wanted.min(checkpoints.len() - 1);
+        let mut state = checkpoints[saved].clone();
+        let lines = self
+            .content
+            .texts_from(saved * CHECKPOINT_INTERVAL)
+            .take(index - saved * CHECKPOINT_INTERVAL);
+        for (i, line) in (saved * CHECKPOINT_INTERVAL..).zip(lines) {
+            highlight(&mut state, &highlighter, &line);
+            let next = i + 1;
+            if next % CHECKPOINT_INTERVAL == 0 && next / CHECKPOINT_INTERVAL == checkpoints.len() {
+                checkpoints.push(state.clone());
+            }
+        }
+        state
+    }
+}
+
+/// Highlight `line` and move `state` to the next line.
+fn highlight(state: &mut Checkpoint, highlighter: &Highlighter, line: &str) -> Vec<Span> {
+    // Syntaxes are loaded for lines with their line ending.
+    let line = format!("{}\n", line);
+    let Ok(ops) = state.parse.parse_line(&line, syntax_set()) else {
+        return vec![Span::raw(line.trim_end_matches('\n'))];
+    };
+    // Join neighbouring ranges of the same style, themes style most punctuation the same.
+    let mut spans: Vec<Span> = vec![];
+    for (style, text) in HighlightIterator::new(&mut state.highlight, &ops, &line, highlighter) {
+        let style = content_style(style);
+        let text = text.trim_end_matches('\n');
+        match spans.last_mut() {
+            Some(last) if last.style == style => last.text += text,
+            _ if text.is_empty() => {}
+            _ => spans.push(Span::new(text, style)),
+        }
+    }
+    spans
+}
+
+/// Convert a `syntect` style to a [`ContentStyle`], backgrounds are left to the terminal.
+fn content_style(style: Style) -> ContentStyle {
+    let mut content_style = ContentStyle::new();
+    content_style.foreground_color = Some(Color::Rgb {
+        r: style.foreground.r,
+        g: style.foreground.g,
+        b: style.foreground.b,
+    });
+    for (font_style, attribute) in [
+        (FontStyle::BOLD, Attribute::Bold),
+        (FontStyle::ITALIC, Attribute::Italic),
+        (FontStyle::UNDERLINE, Attribute::Underlined),
+    ] {
+        if style.font_style.contains(font_style) {
+            content_style.attributes.set(attribute);
+        }
+    }
+    content_style
+}
+
+impl<C: ContentSource> ContentSource for HighlightedSource<C> {
+    fn poll(&mut self) -> bool {
+        self.content.poll()
+    }
+
+    fn is_complete(&self) -> bool {
+        self.content.is_complete()
+    }
+
+    fn progress(&self) -> Option<f32> {
+        self.content.progress()
+    }
+
+    fn line_count(&self) -> usize {
+        self.content.line_count()
+    }
+
+    fn line(&self, index: usize) -> Option<Cow<'_, str>> {
+        self.content.line(index)
+    }
+
+    fn lines_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        self.content.lines_from(start)
+    }
+
+    fn spans_from(&self, start: usize) -> Box<dyn Iterator<Item = Vec<Span>> + '_> {
+        let mut state = self.state_at(start.min(self.line_count()));
+        let highlighter = Highlighter::new(self.theme);
+        Box::new(
+            self.content
+                .texts_from(start)
+                .map(move |line| highlight(&mut state, &highlighter, &line)),
+        )
+    }
+
+    fn texts_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        self.content.texts_from(start)
+    }
+
+    fn line_at_byte(&self, offset: usize) -> Option<usize> {
+        self.content.line_at_byte(offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextSource;
+
+    /// A block comment over more lines than a checkpoint interval, and code after it.
+    fn content() -> HighlightedSource<TextSource> {
+        let mut text = "/*\n".to_string();
+        for i in 0..100 {
+            text.push_str(&format!("comment {}\n", i));
+        }
+        text.push_str("*/\nfn main() {}\n");
+        HighlightedSource::new(TextSource::new(text), "rs")
+    }
+
+    #[test]
+    fn spans_keep_the_text() {
+        let content = content();
+        let texts: Vec<String> = content
+            .spans_from(0)
+            .map(|spans| spans.into_iter().map(|span| span.text).collect())
+            .collect();
+        let plain: Vec<String> = content.texts_from(0).map(String::from).collect();
+        assert_eq!(texts, plain);
+        assert_eq!(texts[102], "fn main() {}");
+    }
+
+    #[test]
+    fn state_is_kept_across_checkpoints() {
+        let content = content();
+        let from_start: Vec<Vec<Span>> = content.spans_from(0).collect();
+        for index in [1, 63, 64, 70, 101, 102] {
+            assert_eq!(content.spans_from(index).next().unwrap(), from_start[index]);
+        }
+        // The comment and the code are styled differently.
+        assert_ne!(from_start[70][0].style, from_start[102][0].style);
+    }
+}
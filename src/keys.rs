@@ -0,0 +1,329 @@
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use crate::{CommandArgs, CommandType, State};
+
+/// A key with modifiers, like `Ctrl-f`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    /// The key.
+    pub code: KeyCode,
+    /// Modifiers that must be held down.
+    ///
+    /// `SHIFT` is not used for characters and `BackTab`, since the key already tells it.
+    /// Other keys keep it, e.g. `Shift-Up` is a different binding than `Up`.
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Create a [`KeyBinding`].
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        Self { code, modifiers }
+    }
+
+    /// `Ctrl` + `c`
+    pub fn ctrl(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    /// `Alt` + `c`
+    pub fn alt(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::ALT)
+    }
+}
+
+impl From<KeyCode> for KeyBinding {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(event: KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift-")?;
+        }
+        write!(f, "{}", key_name(self.code))
+    }
+}
+
+/// Name of a key, as shown in help text.
+pub(crate) fn key_name(code: KeyCode) -> String {
+    match code {
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "BackTab".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
+        KeyCode::Insert => "Insert".to_string(),
+        KeyCode::F(n) => format!("F{}", n),
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Null => "Null".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::CapsLock => "CapsLock".to_string(),
+        KeyCode::NumLock => "NumLock".to_string(),
+        KeyCode::ScrollLock => "ScrollLock".to_string(),
+        KeyCode::PrintScreen => "PrintScreen".to_string(),
+        KeyCode::Pause => "Pause".to_string(),
+        KeyCode::Menu => "Menu".to_string(),
+        KeyCode::KeypadBegin => "KeypadBegin".to_string(),
+        KeyCode::Media(_) => "MediaKey".to_string(),
+        KeyCode::Modifier(_) => "ModifierKey".to_string(),
+    }
+}
+
+/// Name of a key sequence, as shown in help text.
+pub(crate) fn sequence_name(keys: &[KeyBinding]) -> String {
+    let plain = keys
+        .iter()
+        .all(|key| key.modifiers.is_empty() && matches!(key.code, KeyCode::Char(c) if c != ' '));
+    keys.iter()
+        .map(|key| key.to_string())
+        .collect::<Vec<String>>()
+        .join(if plain { "" } else { " " })
+}
+
+/// Keys that are typed but did not run a command yet.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PendingKeys {
+    /// Numeric prefix, like `5` in `5j`.
+    count: Option<usize>,
+    keys: Vec<KeyBinding>,
+    /// When the last key of `keys` was pressed.
+    since: Option<Instant>,
+}
+
+impl PendingKeys {
+    pub(crate) fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl CommandType {
+    /// Sequence of keys without modifiers, like `gg`.
+    pub fn sequence(keys: &str) -> Self {
+        Self::Keys(
+            keys.chars()
+                .map(|c| KeyBinding::from(KeyCode::Char(c)))
+                .collect(),
+        )
+    }
+
+    /// `Ctrl` + `c`
+    pub fn ctrl(c: char) -> Self {
+        Self::Keys(vec![KeyBinding::ctrl(c)])
+    }
+
+    /// `Alt` + `c`
+    pub fn alt(c: char) -> Self {
+        Self::Keys(vec![KeyBinding::alt(c)])
+    }
+
+    /// Get the key sequence of the command type, if it is not a colon command.
+    pub(crate) fn keys(&self) -> Option<Vec<KeyBinding>> {
+        match self {
+            CommandType::Key(code) => Some(vec![KeyBinding::from(*code)]),
+            CommandType::Keys(keys) => Some(keys.clone()),
+            CommandType::Colon(_) => None,
+        }
+    }
+}
+
+impl<'a> State<'a> {
+    /// Find and execute command matching with pressed key.
+    ///
+    /// Keys of a sequence are kept until the sequence is complete, or until
+    /// [`State::key_timeout`] passes if a shorter sequence matches too.
+    /// Digits before a sequence are passed as [`CommandArgs::count`].
+    pub fn match_key_event(&mut self, event: KeyEvent) -> bool {
+        if event.kind == KeyEventKind::Release {
+            return false;
+        }
+        let key = KeyBinding::from(event);
+
+        if self.pending_keys.keys.is_empty() {
+            if let (KeyCode::Char(digit @ '0'..='9'), true) = (key.code, key.modifiers.is_empty()) {
+                let counting = self.pending_keys.count.is_some() || digit != '0';
+                if counting && self.find_sequences(&[key]).is_empty() {
+                    let count = self.pending_keys.count.unwrap_or(0);
+                    let digit = digit.to_digit(10).unwrap() as usize;
+                    self.pending_keys.count = Some(count.saturating_mul(10).saturating_add(digit));
+                    return false;
+                }
+            }
+        }
+
+        self.pending_keys.keys.push(key);
+        self.pending_keys.since = Some(Instant::now());
+
+        let found = self.find_sequences(&self.pending_keys.keys);
+        let exact = found
+            .iter()
+            .find(|(len, _)| *len == self.pending_keys.keys.len());
+        let longer = found
+            .iter()
+            .any(|(len, _)| *len > self.pending_keys.keys.len());
+        match (exact, longer) {
+            (Some((_, index)), false) => {
+                let index = *index;
+                self.execute_pending(index)
+            }
+            (_, true) => false,
+            (None, false) => {
+                let keys = std::mem::take(&mut self.pending_keys.keys);
+                self.pending_keys.clear();
+                // Try the last key on its own, like `x` of an unknown `gx`.
+                match keys.len() {
+                    1 => false,
+                    _ => self.match_key_event(event),
+                }
+            }
+        }
+    }
+
+    /// How long to wait before the pending keys time out.
+    ///
+    /// Returns `None` if there are no pending keys.
+    pub(crate) fn pending_keys_timeout(&self) -> Option<Duration> {
+        let since = self.pending_keys.since?;
+        Some(self.key_timeout.saturating_sub(since.elapsed()))
+    }
+
+    /// Execute or drop the pending keys after [`State::key_timeout`].
+    pub(crate) fn pending_keys_timed_out(&mut self) -> bool {
+        let found = self.find_sequences(&self.pending_keys.keys);
+        match found
+            .iter()
+            .find(|(len, _)| *len == self.pending_keys.keys.len())
+        {
+            Some((_, index)) => {
+                let index = *index;
+                self.execute_pending(index)
+            }
+            None => {
+                self.pending_keys.clear();
+                false
+            }
+        }
+    }
+
+    /// Find commands with a key sequence that starts with `keys`.
+    ///
+    /// Returns length of the sequence and index of the command.
+    fn find_sequences(&self, keys: &[KeyBinding]) -> Vec<(usize, usize)> {
+        self.commands
+            .0
+            .iter()
+            .enumerate()
+            .flat_map(|(index, command)| {
+                command
+                    .cmd
+                    .iter()
+                    .filter_map(CommandType::keys)
+                    .filter(|sequence| sequence.starts_with(keys))
+                    .map(move |sequence| (sequence.len(), index))
+            })
+            .collect()
+    }
+
+    fn execute_pending(&mut self, index: usize) -> bool {
+        let args = CommandArgs {
+            count: self.pending_keys.count,
+            ..Default::default()
+        };
+        self.pending_keys.clear();
+        let command = self.commands.0[index].clone();
+        command.execute(self, &args).unwrap_or_else(|err| {
+            self.message = Some(err);
+            true
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shift_is_dropped_for_characters_and_back_tab() {
+        let key = KeyBinding::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(key, KeyBinding::from(KeyCode::Char('G')));
+
+        let key = KeyBinding::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(key, KeyBinding::from(KeyCode::BackTab));
+
+        let key = KeyBinding::new(KeyCode::Down, KeyModifiers::SHIFT);
+        assert_eq!(key.modifiers, KeyModifiers::SHIFT);
+    }
+
+    #[test]
+    fn key_binding_names() {
+        assert_eq!(KeyBinding::ctrl('f').to_string(), "Ctrl-f");
+        assert_eq!(KeyBinding::alt('<').to_string(), "Alt-<");
+        assert_eq!(
+            KeyBinding::new(KeyCode::Up, KeyModifiers::SHIFT).to_string(),
+            "Shift-Up"
+        );
+        assert_eq!(KeyBinding::from(KeyCode::Char(' ')).to_string(), "Space");
+        assert_eq!(KeyBinding::from(KeyCode::F(5)).to_string(), "F5");
+    }
+
+    #[test]
+    fn sequence_names() {
+        let CommandType::Keys(keys) = CommandType::sequence("gg") else {
+            unreachable!()
+        };
+        assert_eq!(sequence_name(&keys), "gg");
+
+        let keys = [KeyBinding::ctrl('x'), KeyBinding::ctrl('c')];
+        assert_eq!(sequence_name(&keys), "Ctrl-x Ctrl-c");
+
+        let keys = [
+            KeyBinding::from(KeyCode::Char(' ')),
+            KeyBinding::from(KeyCode::Char('q')),
+        ];
+        assert_eq!(sequence_name(&keys), "Space q");
+    }
+
+    #[test]
+    fn command_type_keys() {
+        assert_eq!(
+            CommandType::Key(KeyCode::Home).keys(),
+            Some(vec![KeyBinding::from(KeyCode::Home)])
+        );
+        assert_eq!(
+            CommandType::ctrl('f').keys(),
+            Some(vec![KeyBinding::ctrl('f')])
+        );
+        assert_eq!(CommandType::Colon("quit".to_string()).keys(), None);
+    }
+}
@@ -8,6 +8,9 @@ Cross-platform, customizable terminal pager library for rust.
 # Usage
 See: [examples]
 
+# Features
+- `syntax-highlighting`: highlight content with [`HighlightedSource`].
+
 [examples]: https://github.com/ketenburhan/pager-rs/tree/main/examples
 [`custom commands`]: Command
 */
@@ -16,63 +19,139 @@ See: [examples]
 
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode, KeyEvent, MouseEvent, MouseEventKind},
-    execute, queue,
+    event::{
+        self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind,
+    },
+    execute,
     style::Print,
-    terminal::{self, disable_raw_mode, enable_raw_mode, Clear, ClearType},
+    terminal,
 };
-use std::io::{stdin, stdout, Write};
+use std::{io::stdout, ops::Range, time::Duration};
 
 mod status_bar;
 pub use status_bar::*;
 mod state;
 pub use state::*;
+mod search;
+pub use search::*;
+mod filter;
+pub use filter::*;
+mod marks;
+pub use marks::*;
+mod content;
+pub use content::*;
+mod mmap;
+pub use mmap::*;
+mod span;
+pub use span::*;
+#[cfg(feature = "syntax-highlighting")]
+mod highlight;
+#[cfg(feature = "syntax-highlighting")]
+pub use highlight::*;
+mod colon;
+pub use colon::*;
+mod backend;
+pub use backend::*;
+mod buffer;
+pub use buffer::*;
+mod keys;
+pub use keys::*;
+mod pager;
+pub use pager::*;
+
+mod ansi;
+mod line_editor;
+mod presets;
+mod text;
+mod wrap;
+
+use line_editor::{push_history, EditAction, LineEditor};
+
+/// How long [`run`] waits for input before pulling new lines from an incomplete [`ContentSource`].
+const CONTENT_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Run a [`State`]
 pub fn run(state: &mut State) -> std::io::Result<()> {
-    let mut out = stdout();
-    disable_raw_mode()?;
-    execute!(
-        out,
-        terminal::Clear(ClearType::All),
-        cursor::MoveTo(0, 0),
-        Print(state.get_visible()),
-        cursor::MoveTo(0, state.size.1 - state.status_bar.line_layouts.len() as u16),
-        Print(state.status_bar.get_visible(state))
-    )?;
-    enable_raw_mode()?;
+    state.poll_content();
+    state.backend.borrow_mut().enable_raw_mode()?;
+    let result = run_loop(state);
+    state.backend.borrow_mut().disable_raw_mode()?;
+    result
+}
+
+fn run_loop(state: &mut State) -> std::io::Result<()> {
+    draw(state)?;
 
     while state.running {
-        let read_event = event::read()?;
+        let timeout = match (state.content.is_complete(), state.pending_keys_timeout()) {
+            (true, timeout) => timeout,
+            (false, timeout) => Some(timeout.map_or(CONTENT_POLL_INTERVAL, |timeout| {
+                timeout.min(CONTENT_POLL_INTERVAL)
+            })),
+        };
+        // Lines are filtered and matches are counted between events.
+        let counting = state
+            .search
+            .as_ref()
+            .is_some_and(|search| !search.is_counted())
+            || state
+                .filter
+                .as_ref()
+                .is_some_and(|filter| !filter.is_checked());
+        let timeout = match counting {
+            true => Some(Duration::ZERO),
+            false => timeout,
+        };
+        if let Some(timeout) = timeout {
+            if !state.backend.borrow_mut().poll(timeout)? {
+                let mut flush =
+                    state.poll_content() || state.check_filter() || state.count_matches();
+                if state.pending_keys_timeout() == Some(Duration::ZERO) {
+                    flush |= state.pending_keys_timed_out();
+                }
+                if flush {
+                    draw(state)?;
+                }
+                continue;
+            }
+        }
+        let read_event = state.backend.borrow_mut().read()?;
+        let key_press = matches!(read_event, Event::Key(key) if key.kind != KeyEventKind::Release);
+        let had_message = key_press && state.message.take().is_some();
+        if key_press
+            || matches!(
+                read_event,
+                Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::ScrollUp | MouseEventKind::ScrollDown,
+                    ..
+                })
+            )
+        {
+            state.stop_following();
+            // The view stays where it is moved to while the filter is checked.
+            if let Some(filter) = &mut state.filter {
+                filter.top = None;
+            }
+        }
         let flush = match read_event {
-            Event::Key(KeyEvent { code, .. }) => match code {
-                KeyCode::Char(':') => {
-                    disable_raw_mode()?;
-                    execute!(
-                        out,
-                        cursor::MoveTo(0, state.size.1 - 1),
-                        Clear(ClearType::CurrentLine),
-                        cursor::Show,
-                        Print(":")
-                    )?;
-                    let mut buf = String::new();
-                    stdin().read_line(&mut buf)?;
-                    let buf = buf.lines().next().unwrap();
-
-                    let found = state.commands.0.clone().into_iter().find(
-                        |command| matches!(command, Command { cmd, .. } if cmd.contains(&CommandType::Colon(buf.to_string()))),
-                    );
-                    let retrn = if let Some(Command { func, .. }) = found {
-                        func(state)
-                    } else {
-                        false
-                    };
-
-                    execute!(out, Print(retrn), cursor::Hide)?;
-                    enable_raw_mode()?;
-                    retrn
+            Event::Key(key) => match KeyBinding::from(key) {
+                KeyBinding {
+                    code: KeyCode::Char(':'),
+                    modifiers: KeyModifiers::NONE,
+                } if key.kind != KeyEventKind::Release => {
+                    state.pending_keys.clear();
+                    if let Some(input) = read_input(
+                        state,
+                        ":",
+                        |_, _| false,
+                        Some(|state, input| state.complete_colon(input)),
+                    )? {
+                        state.execute_colon(&input);
+                    }
+                    // The prompt is drawn over the status bar.
+                    true
                 }
-                code => state.match_key_event(code),
+                _ => state.match_key_event(key),
             },
             Event::Mouse(ev) => match ev {
                 MouseEvent {
@@ -91,48 +170,213 @@ pub fn run(state: &mut State) -> std::io::Result<()> {
             }
             _ => false,
         };
-        if flush {
-            disable_raw_mode()?;
-            queue!(
-                out,
-                cursor::MoveTo(0, 0),
-                terminal::Clear(ClearType::All),
-                Print(state.get_visible()),
-                cursor::MoveTo(0, state.size.1 - state.status_bar.line_layouts.len() as u16),
-                Print(state.status_bar.get_visible(state)),
-            )?;
-            out.flush()?;
-            enable_raw_mode()?;
+        let flush = state.poll_content() || state.check_filter() || state.count_matches() || flush;
+        if flush || had_message || state.message.is_some() {
+            draw(state)?;
         }
     }
 
-    disable_raw_mode()?;
-
     Ok(())
 }
 
+/// Redraw the whole screen.
+fn draw(state: &State) -> std::io::Result<()> {
+    let mut backend = state.backend.borrow_mut();
+    backend.draw(&frame(state), content_rows(state))?;
+    backend.flush()
+}
+
+/// Render the content and the status bar of `state`.
+fn frame(state: &State) -> Buffer {
+    let mut buffer = Buffer::new(state.size.0, state.size.1);
+    for (row, spans) in state.visible_lines().iter().enumerate() {
+        buffer.print_spans(row as u16, spans);
+    }
+    let status_bar = state.status_bar.get_visible(state);
+    buffer.print_spans(
+        content_rows(state).end,
+        &[Span::new(status_bar.content(), *status_bar.style())],
+    );
+    buffer
+}
+
+/// Rows of the screen that show the content.
+fn content_rows(state: &State) -> Range<u16> {
+    0..state.text_height() as u16
+}
+
+/// Read a line of input from the user, on the last line of the screen.
+///
+/// `prefix` is printed before the input, e.g. `":"` or `"/"`.
+/// Submitted inputs are kept in a history for each `prefix`, that can be navigated with
+/// `Up`/`Down` keys.
+///
+/// Returns `None` if the input is cancelled with `Esc`.
+pub fn prompt(state: &mut State, prefix: &str) -> std::io::Result<Option<String>> {
+    read_input(state, prefix, |_, _| false, None)
+}
+
+/// Same as [`prompt`], but `on_change` is called with the input every time it is edited.
+///
+/// The screen is redrawn if `on_change` returns true.
+pub fn prompt_with<F>(
+    state: &mut State,
+    prefix: &str,
+    on_change: F,
+) -> std::io::Result<Option<String>>
+where
+    F: FnMut(&mut State, &str) -> bool,
+{
+    read_input(state, prefix, on_change, None)
+}
+
+/// Show `prefix` on the last line of the screen and wait for a key press.
+///
+/// Returns `None` if it is cancelled with `Esc`.
+pub fn prompt_key(state: &mut State, prefix: &str) -> std::io::Result<Option<KeyEvent>> {
+    loop {
+        let event = {
+            let row = state.size.1.saturating_sub(1);
+            let mut buffer = frame(state);
+            buffer.clear_row(row);
+            buffer.print_spans(row, &[Span::raw(prefix)]);
+            let mut backend = state.backend.borrow_mut();
+            backend.draw(&buffer, content_rows(state))?;
+            backend.flush()?;
+            backend.read()?
+        };
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                return Ok(Some(key).filter(|key| key.code != KeyCode::Esc));
+            }
+            Event::Resize(x, y) => state.size = (x, y),
+            _ => {}
+        }
+    }
+}
+
+/// Completer of [`read_input`].
+type InputCompleter = fn(&State, &str) -> (usize, Vec<String>);
+
+/// Implementation of [`prompt_with`], with optional `Tab` completion.
+fn read_input<F>(
+    state: &mut State,
+    prefix: &str,
+    mut on_change: F,
+    completer: Option<InputCompleter>,
+) -> std::io::Result<Option<String>>
+where
+    F: FnMut(&mut State, &str) -> bool,
+{
+    let history = state.history.get(prefix).cloned().unwrap_or_default();
+    let mut editor = LineEditor::new(history);
+
+    let input = loop {
+        let (line, column) = editor.visible(prefix, state.size.0 as usize);
+        let event = {
+            let row = state.size.1.saturating_sub(1);
+            let mut buffer = frame(state);
+            buffer.clear_row(row);
+            buffer.print_spans(row, &[Span::raw(line)]);
+            let mut backend = state.backend.borrow_mut();
+            backend.draw(&buffer, content_rows(state))?;
+            backend.set_cursor(Some((column as u16, row)))?;
+            backend.flush()?;
+            backend.read()?
+        };
+
+        match event {
+            Event::Key(KeyEvent {
+                code: code @ (KeyCode::Tab | KeyCode::BackTab),
+                kind: KeyEventKind::Press | KeyEventKind::Repeat,
+                ..
+            }) => {
+                let Some(completer) = completer else {
+                    continue;
+                };
+                let backward = code == KeyCode::BackTab;
+                if editor.complete(backward, |input| completer(state, input))
+                    && on_change(state, &editor.input())
+                {
+                    draw(state)?;
+                }
+            }
+            Event::Key(key) if key.kind != KeyEventKind::Release => match editor.handle_key(key) {
+                EditAction::Continue(true) => {
+                    if on_change(state, &editor.input()) {
+                        draw(state)?;
+                    }
+                }
+                EditAction::Continue(false) => {}
+                EditAction::Submit(input) => break Some(input),
+                EditAction::Cancel => break None,
+            },
+            Event::Resize(x, y) => {
+                state.size = (x, y);
+                draw(state)?;
+            }
+            _ => {}
+        }
+    };
+    state.backend.borrow_mut().set_cursor(None)?;
+
+    if let Some(input) = &input {
+        push_history(
+            state.history.entry(prefix.to_string()).or_default(),
+            input.clone(),
+        );
+    }
+    Ok(input)
+}
+
 /// Setup terminal for running [`State`].
 /// Enter alternate screen, enable mouse capture, hide the cursor.
 ///
-/// This function must be called before the [`run`] function.
+/// This function must be called before the [`run`] function,
+/// [`Pager`] calls it and [`finish`] automatically.
 pub fn init() -> std::io::Result<()> {
+    init_with(&PagerOptions::default())
+}
+
+/// Setup terminal for running [`State`] with `options`, see [`init`].
+///
+/// Without [`PagerOptions::alternate_screen`], the lines on the screen are scrolled up into the
+/// scrollback so that the pager is drawn below them.
+pub fn init_with(options: &PagerOptions) -> std::io::Result<()> {
+    CrosstermBackend::forget_screen();
     let mut out = stdout();
-    execute!(
-        out,
-        terminal::EnterAlternateScreen,
-        event::EnableMouseCapture,
-        cursor::Hide,
-    )
+    if options.alternate_screen {
+        execute!(out, terminal::EnterAlternateScreen)?;
+    } else {
+        let (_, height) = terminal::size()?;
+        execute!(out, Print("\n".repeat(height as usize)))?;
+    }
+    execute!(out, event::EnableMouseCapture, cursor::Hide)
 }
 
 /// Undo [`init`].
 /// Leave alternate screen, disable mouse capture, show the cursor.
 pub fn finish() -> std::io::Result<()> {
+    finish_with(&PagerOptions::default())
+}
+
+/// Undo [`init_with`].
+///
+/// Without [`PagerOptions::alternate_screen`], the last screen is left in place and only the
+/// status bar line is cleared.
+pub fn finish_with(options: &PagerOptions) -> std::io::Result<()> {
+    CrosstermBackend::forget_screen();
     let mut out = stdout();
-    execute!(
-        out,
-        event::DisableMouseCapture,
-        terminal::LeaveAlternateScreen,
-        cursor::Show
-    )
+    execute!(out, event::DisableMouseCapture)?;
+    if options.alternate_screen {
+        execute!(out, terminal::LeaveAlternateScreen)?;
+    } else {
+        let (_, height) = terminal::size()?;
+        execute!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1)),
+            terminal::Clear(terminal::ClearType::CurrentLine),
+        )?;
+    }
+    execute!(out, cursor::Show)
 }
@@ -0,0 +1,446 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::text;
+
+/// Maximum number of entries kept in a prompt history.
+const HISTORY_SIZE: usize = 100;
+
+/// Result of a key press in a [`LineEditor`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum EditAction {
+    /// Keep editing, `true` if the input changed.
+    Continue(bool),
+    /// Enter is pressed.
+    Submit(String),
+    /// Editing is cancelled.
+    Cancel,
+}
+
+/// Single line input editor used by [`prompt`](crate::prompt).
+pub(crate) struct LineEditor {
+    input: Vec<char>,
+    /// Cursor position in `input`.
+    cursor: usize,
+    /// Older inputs, the last one is the newest.
+    history: Vec<String>,
+    /// Position in `history` while navigating it, `history.len()` is the current input.
+    history_index: usize,
+    /// The input that was being edited before navigating the history.
+    draft: Vec<char>,
+    completion: Option<Completion>,
+}
+
+/// Tab completion in progress.
+struct Completion {
+    /// Position in input where the completed word starts.
+    start: usize,
+    candidates: Vec<String>,
+    /// Index of the candidate in input.
+    index: usize,
+}
+
+impl LineEditor {
+    pub fn new(history: Vec<String>) -> Self {
+        Self {
+            input: vec![],
+            cursor: 0,
+            history_index: history.len(),
+            history,
+            draft: vec![],
+            completion: None,
+        }
+    }
+
+    pub fn input(&self) -> String {
+        self.input.iter().collect()
+    }
+
+    /// Handle a key press.
+    pub fn handle_key(&mut self, key: KeyEvent) -> EditAction {
+        self.completion = None;
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        // Emacs style alternatives of movement and deletion keys.
+        let code = match (key.code, ctrl) {
+            (KeyCode::Char('h'), true) => KeyCode::Backspace,
+            (KeyCode::Char('d'), true) => KeyCode::Delete,
+            (KeyCode::Char('b'), true) => KeyCode::Left,
+            (KeyCode::Char('f'), true) => KeyCode::Right,
+            (KeyCode::Char('a'), true) => KeyCode::Home,
+            (KeyCode::Char('e'), true) => KeyCode::End,
+            (KeyCode::Char('p'), true) => KeyCode::Up,
+            (KeyCode::Char('n'), true) => KeyCode::Down,
+            (code, _) => code,
+        };
+        let before = self.input.clone();
+        match code {
+            KeyCode::Enter => return EditAction::Submit(self.input()),
+            KeyCode::Esc => return EditAction::Cancel,
+            KeyCode::Char('c') if ctrl => return EditAction::Cancel,
+            KeyCode::Backspace if self.input.is_empty() => return EditAction::Cancel,
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.input.remove(self.cursor);
+            }
+            KeyCode::Delete if self.cursor < self.input.len() => {
+                self.input.remove(self.cursor);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.input.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.input.len(),
+            KeyCode::Char('u') if ctrl => {
+                self.input.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            KeyCode::Char('k') if ctrl => self.input.truncate(self.cursor),
+            KeyCode::Char('w') if ctrl => {
+                let mut start = self.cursor;
+                while start > 0 && self.input[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                while start > 0 && !self.input[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                self.input.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            KeyCode::Up => self.history_move(-1),
+            KeyCode::Down => self.history_move(1),
+            KeyCode::Char(c) if !ctrl => {
+                self.input.insert(self.cursor, c);
+                self.cursor += 1;
+            }
+            _ => {}
+        }
+        EditAction::Continue(self.input != before)
+    }
+
+    /// Complete the word before the cursor, or replace it with the next candidate if the
+    /// last key press was a completion too.
+    ///
+    /// `completer` gets the input before the cursor, and returns the position where the word
+    /// starts and the candidates.
+    ///
+    /// Returns true if the input changed.
+    pub fn complete<F>(&mut self, backward: bool, completer: F) -> bool
+    where
+        F: FnOnce(&str) -> (usize, Vec<String>),
+    {
+        match &mut self.completion {
+            Some(completion) => {
+                let len = completion.candidates.len();
+                completion.index = match backward {
+                    true => (completion.index + len - 1) % len,
+                    false => (completion.index + 1) % len,
+                };
+            }
+            None => {
+                let (start, candidates) =
+                    completer(&self.input[..self.cursor].iter().collect::<String>());
+                if candidates.is_empty() || start > self.cursor {
+                    return false;
+                }
+                let index = if backward { candidates.len() - 1 } else { 0 };
+                self.completion = Some(Completion {
+                    start,
+                    candidates,
+                    index,
+                });
+            }
+        }
+        let Some(completion) = &self.completion else {
+            return false;
+        };
+        let candidate = completion.candidates[completion.index].chars();
+        let before = self.input.clone();
+        let end = self.input.len() - self.cursor;
+        self.input.splice(completion.start..self.cursor, candidate);
+        self.cursor = self.input.len() - end;
+        self.input != before
+    }
+
+    fn history_move(&mut self, step: isize) {
+        let Some(index) = self.history_index.checked_add_signed(step) else {
+            return;
+        };
+        if index > self.history.len() {
+            return;
+        }
+        if self.history_index == self.history.len() {
+            self.draft = self.input.clone();
+        }
+        self.history_index = index;
+        self.input = match self.history.get(index) {
+            Some(entry) => entry.chars().collect(),
+            None => self.draft.clone(),
+        };
+        self.cursor = self.input.len();
+    }
+
+    /// Get the visible part of `prefix` and the input in `width` columns,
+    /// and the column of the cursor.
+    ///
+    /// The line is scrolled so that the column after the cursor is visible too, wide
+    /// characters that are cut at the left edge are replaced with spaces.
+    pub fn visible(&self, prefix: &str, width: usize) -> (String, usize) {
+        let before: String = prefix
+            .chars()
+            .chain(self.input[..self.cursor].iter().copied())
+            .collect();
+        let mut line = before.clone();
+        line.extend(&self.input[self.cursor..]);
+        let cursor = text::width(&before);
+        let offset = (cursor + 1).saturating_sub(width);
+        let visible = text::columns(&line, offset, width)
+            .into_iter()
+            .map(|(_, grapheme)| grapheme)
+            .collect();
+        (visible, cursor.saturating_sub(offset))
+    }
+}
+
+/// Add `entry` to the end of `history`.
+pub(crate) fn push_history(history: &mut Vec<String>, entry: String) {
+    if entry.is_empty() || history.last() == Some(&entry) {
+        return;
+    }
+    history.push(entry);
+    if history.len() > HISTORY_SIZE {
+        history.remove(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn editor(input: &str) -> LineEditor {
+        let mut editor = LineEditor::new(vec![]);
+        for c in input.chars() {
+            editor.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        editor
+    }
+
+    fn press(editor: &mut LineEditor, code: KeyCode) -> EditAction {
+        editor.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn ctrl(editor: &mut LineEditor, c: char) -> EditAction {
+        editor.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+    }
+
+    /// Completes the last word with the words that start with it.
+    fn completer(input: &str) -> (usize, Vec<String>) {
+        let start = input.rfind(' ').map_or(0, |i| i + 1);
+        let words = ["bar", "baz", "foo"];
+        let candidates = words
+            .iter()
+            .filter(|word| word.starts_with(&input[start..]))
+            .map(|word| word.to_string())
+            .collect();
+        (input[..start].chars().count(), candidates)
+    }
+
+    #[test]
+    fn tab_cycles_through_candidates() {
+        let mut editor = editor("x b");
+        assert!(editor.complete(false, completer));
+        assert_eq!(editor.input(), "x bar");
+        assert!(editor.complete(false, completer));
+        assert_eq!(editor.input(), "x baz");
+        assert!(editor.complete(false, completer));
+        assert_eq!(editor.input(), "x bar");
+    }
+
+    #[test]
+    fn back_tab_cycles_backward() {
+        let mut editor = editor("b");
+        assert!(editor.complete(true, completer));
+        assert_eq!(editor.input(), "baz");
+        assert!(editor.complete(true, completer));
+        assert_eq!(editor.input(), "bar");
+        assert!(editor.complete(true, completer));
+        assert_eq!(editor.input(), "baz");
+        editor.complete(false, completer);
+        assert_eq!(editor.input(), "bar");
+    }
+
+    #[test]
+    fn completion_before_the_cursor() {
+        let mut editor = editor("f x");
+        press(&mut editor, KeyCode::Left);
+        press(&mut editor, KeyCode::Left);
+        assert!(editor.complete(false, completer));
+        assert_eq!(editor.input(), "foo x");
+        assert_eq!(editor.cursor, 3);
+    }
+
+    #[test]
+    fn key_press_ends_the_completion() {
+        let mut editor = editor("b");
+        editor.complete(false, completer);
+        press(&mut editor, KeyCode::Char(' '));
+        press(&mut editor, KeyCode::Char('b'));
+        editor.complete(false, completer);
+        assert_eq!(editor.input(), "bar bar");
+    }
+
+    #[test]
+    fn no_candidates() {
+        let mut missing = editor("q");
+        assert!(!missing.complete(false, completer));
+        assert_eq!(missing.input(), "q");
+        // The only candidate is already typed.
+        let mut typed = editor("foo");
+        assert!(!typed.complete(false, completer));
+    }
+
+    #[test]
+    fn typing_and_submitting() {
+        let mut typed = editor("ab");
+        assert_eq!(
+            press(&mut typed, KeyCode::Char('c')),
+            EditAction::Continue(true)
+        );
+        assert_eq!(
+            press(&mut typed, KeyCode::Left),
+            EditAction::Continue(false)
+        );
+        assert_eq!(
+            press(&mut typed, KeyCode::Enter),
+            EditAction::Submit("abc".to_string())
+        );
+        assert_eq!(press(&mut typed, KeyCode::Esc), EditAction::Cancel);
+        assert_eq!(ctrl(&mut typed, 'c'), EditAction::Cancel);
+    }
+
+    #[test]
+    fn backspace_on_empty_input_cancels() {
+        let mut typed = editor("a");
+        assert_eq!(
+            press(&mut typed, KeyCode::Backspace),
+            EditAction::Continue(true)
+        );
+        assert_eq!(press(&mut typed, KeyCode::Backspace), EditAction::Cancel);
+    }
+
+    #[test]
+    fn moving_the_cursor() {
+        let mut typed = editor("abcd");
+        press(&mut typed, KeyCode::Home);
+        press(&mut typed, KeyCode::Left);
+        assert_eq!(typed.cursor, 0);
+        press(&mut typed, KeyCode::Right);
+        press(&mut typed, KeyCode::Delete);
+        assert_eq!(typed.input(), "acd");
+        press(&mut typed, KeyCode::Backspace);
+        assert_eq!(typed.input(), "cd");
+        press(&mut typed, KeyCode::End);
+        press(&mut typed, KeyCode::Right);
+        assert_eq!(typed.cursor, 2);
+        assert_eq!(
+            press(&mut typed, KeyCode::Delete),
+            EditAction::Continue(false)
+        );
+
+        // Emacs style keys.
+        ctrl(&mut typed, 'a');
+        assert_eq!(typed.cursor, 0);
+        ctrl(&mut typed, 'f');
+        assert_eq!(typed.cursor, 1);
+        ctrl(&mut typed, 'd');
+        assert_eq!(typed.input(), "c");
+        ctrl(&mut typed, 'e');
+        ctrl(&mut typed, 'b');
+        ctrl(&mut typed, 'h');
+        assert_eq!(typed.input(), "c");
+        ctrl(&mut typed, 'e');
+        ctrl(&mut typed, 'h');
+        assert_eq!(typed.input(), "");
+    }
+
+    #[test]
+    fn deleting_to_the_start_and_end() {
+        let mut typed = editor("abcd");
+        press(&mut typed, KeyCode::Left);
+        press(&mut typed, KeyCode::Left);
+        ctrl(&mut typed, 'k');
+        assert_eq!(typed.input(), "ab");
+        press(&mut typed, KeyCode::Left);
+        ctrl(&mut typed, 'u');
+        assert_eq!(typed.input(), "b");
+        assert_eq!(typed.cursor, 0);
+    }
+
+    #[test]
+    fn ctrl_w_deletes_the_word_before_the_cursor() {
+        let mut typed = editor("foo bar  baz");
+        ctrl(&mut typed, 'w');
+        assert_eq!(typed.input(), "foo bar  ");
+        ctrl(&mut typed, 'w');
+        assert_eq!(typed.input(), "foo ");
+        press(&mut typed, KeyCode::Left);
+        press(&mut typed, KeyCode::Left);
+        ctrl(&mut typed, 'w');
+        assert_eq!(typed.input(), "o ");
+        assert_eq!(typed.cursor, 0);
+        assert_eq!(ctrl(&mut typed, 'w'), EditAction::Continue(false));
+    }
+
+    #[test]
+    fn history_keeps_the_draft() {
+        let mut typed = LineEditor::new(vec!["one".to_string(), "two".to_string()]);
+        press(&mut typed, KeyCode::Char('x'));
+        press(&mut typed, KeyCode::Up);
+        assert_eq!(typed.input(), "two");
+        ctrl(&mut typed, 'p');
+        assert_eq!(typed.input(), "one");
+        press(&mut typed, KeyCode::Up);
+        assert_eq!(typed.input(), "one");
+        assert_eq!(typed.cursor, 3);
+        press(&mut typed, KeyCode::Down);
+        assert_eq!(typed.input(), "two");
+        ctrl(&mut typed, 'n');
+        assert_eq!(typed.input(), "x");
+        press(&mut typed, KeyCode::Down);
+        assert_eq!(typed.input(), "x");
+    }
+
+    #[test]
+    fn history_skips_empty_and_repeated_entries() {
+        let mut history = vec![];
+        for entry in ["a", "a", "", "b", "a"] {
+            push_history(&mut history, entry.to_string());
+        }
+        assert_eq!(history, ["a", "b", "a"]);
+        for i in 0..HISTORY_SIZE {
+            push_history(&mut history, i.to_string());
+        }
+        assert_eq!(history.len(), HISTORY_SIZE);
+        assert_eq!(history[0], "0");
+    }
+
+    #[test]
+    fn visible_scrolls_to_the_cursor() {
+        let mut typed = editor("abcdef");
+        assert_eq!(typed.visible("/", 10), ("/abcdef".to_string(), 7));
+        assert_eq!(typed.visible("/", 5), ("cdef".to_string(), 4));
+        press(&mut typed, KeyCode::Home);
+        assert_eq!(typed.visible("/", 5), ("/abcd".to_string(), 1));
+        assert_eq!(typed.visible("/", 0), (String::new(), 0));
+    }
+
+    #[test]
+    fn visible_counts_wide_characters() {
+        let mut typed = editor("日本語");
+        assert_eq!(typed.visible("/", 10), ("/日本語".to_string(), 7));
+        assert_eq!(typed.visible("/", 5), ("本語".to_string(), 4));
+        // The half of a wide character at the left edge is a space.
+        assert_eq!(typed.visible("/", 6), (" 本語".to_string(), 5));
+        press(&mut typed, KeyCode::Home);
+        press(&mut typed, KeyCode::Right);
+        assert_eq!(typed.visible("/", 10), ("/日本語".to_string(), 3));
+        assert_eq!(typed.visible("/", 4), ("/日 ".to_string(), 3));
+    }
+}
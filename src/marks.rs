@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+
+use crate::{prompt_key, Command, CommandList, CommandType, State};
+
+/// Position in the content that a mark or a jump returns to.
+///
+/// Lines are kept as indices in the content, so they stay the same when it is filtered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mark {
+    /// Index of the line at the top of the screen.
+    pub line: usize,
+    /// Column of the horizontal scroll.
+    pub column: usize,
+}
+
+/// Marks and the jump list of a [`State`].
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Marks {
+    marks: HashMap<char, Mark>,
+    jumps: Vec<Mark>,
+    /// Position in `jumps`, equal to its length unless moving through it.
+    jump_index: usize,
+    /// Position before the last jump, see [`State::jump_previous`].
+    previous: Option<Mark>,
+}
+
+impl Marks {
+    /// Letter of the first mark on `line`.
+    fn letter_at(&self, line: usize) -> Option<char> {
+        self.marks
+            .iter()
+            .filter(|(_, mark)| mark.line == line)
+            .map(|(&letter, _)| letter)
+            .min()
+    }
+
+    /// Add `from` to the jump list, dropping the jumps after the current one.
+    fn record(&mut self, from: Mark) {
+        self.jumps.truncate(self.jump_index);
+        if self.jumps.last() != Some(&from) {
+            self.jumps.push(from);
+        }
+        self.jump_index = self.jumps.len();
+        self.previous = Some(from);
+    }
+}
+
+impl<'a> State<'a> {
+    /// Current position, as it is saved by marks and jumps.
+    fn position(&self) -> Mark {
+        Mark {
+            line: self.content_index(self.pos.1),
+            column: self.pos.0,
+        }
+    }
+
+    /// Move to `mark`, or to the next shown line if its line is filtered out.
+    fn go_to(&mut self, mark: Mark) {
+        self.pos.0 = mark.column;
+        self.scroll_to(self.shown_index(mark.line));
+    }
+
+    /// Save the current position to the jump list, before a large move.
+    pub(crate) fn record_jump(&mut self) {
+        let position = self.position();
+        self.marks.record(position);
+    }
+
+    /// Save the current position as mark `letter`.
+    pub fn set_mark(&mut self, letter: char) {
+        let position = self.position();
+        self.marks.marks.insert(letter, position);
+    }
+
+    /// Get the position of mark `letter`.
+    pub fn get_mark(&self, letter: char) -> Option<Mark> {
+        self.marks.marks.get(&letter).copied()
+    }
+
+    /// Move to mark `letter`.
+    ///
+    /// Returns false if there is no such mark.
+    pub fn jump_to_mark(&mut self, letter: char) -> bool {
+        let Some(mark) = self.get_mark(letter) else {
+            return false;
+        };
+        self.record_jump();
+        self.go_to(mark);
+        true
+    }
+
+    /// Move back to the position before the last jump.
+    ///
+    /// Returns false if there was no jump.
+    pub fn jump_previous(&mut self) -> bool {
+        let Some(previous) = self.marks.previous else {
+            return false;
+        };
+        self.record_jump();
+        self.go_to(previous);
+        true
+    }
+
+    /// Move to the previous position in the jump list.
+    pub fn jump_back(&mut self) -> bool {
+        let position = self.position();
+        let marks = &mut self.marks;
+        if marks.jump_index == marks.jumps.len() {
+            // Keep the current position, so that it can be jumped forward to.
+            if marks.jumps.last() != Some(&position) {
+                marks.jumps.push(position);
+            }
+            marks.jump_index = marks.jumps.len().saturating_sub(1);
+        }
+        if marks.jump_index == 0 {
+            return false;
+        }
+        marks.jump_index -= 1;
+        let mark = marks.jumps[marks.jump_index];
+        self.go_to(mark);
+        true
+    }
+
+    /// Move to the next position in the jump list, after [`State::jump_back`].
+    pub fn jump_forward(&mut self) -> bool {
+        let marks = &mut self.marks;
+        if marks.jump_index + 1 >= marks.jumps.len() {
+            return false;
+        }
+        marks.jump_index += 1;
+        let mark = marks.jumps[marks.jump_index];
+        self.go_to(mark);
+        true
+    }
+
+    /// Letter of a mark on the content line at `index`, shown in the line-number gutter.
+    pub(crate) fn mark_at(&self, index: usize) -> Option<char> {
+        self.marks.letter_at(index)
+    }
+}
+
+/// Read a mark letter, showing `prefix` while waiting for it.
+fn read_mark(state: &mut State, prefix: &str) -> Option<char> {
+    match prompt_key(state, prefix) {
+        Ok(Some(key)) => match key.code {
+            KeyCode::Char(c) if c.is_ascii_alphabetic() || c == '\'' => Some(c),
+            _ => {
+                state.message = Some("Invalid mark".to_string());
+                None
+            }
+        },
+        Ok(None) => None,
+        Err(err) => {
+            state.message = Some(err.to_string());
+            None
+        }
+    }
+}
+
+impl CommandList {
+    /// Default bundle of 'marks' commands.
+    ///
+    /// Includes: `m`, `'`, `Ctrl-o`, `Tab` (`Ctrl-i`) keys
+    pub fn marks() -> Self {
+        use CommandType::*;
+        Self(vec![
+            Command::new(
+                vec![Key(KeyCode::Char('m'))],
+                "Set mark, followed by a letter".to_string(),
+                |state: &mut State| {
+                    match read_mark(state, "mark: ") {
+                        Some('\'') => state.message = Some("Invalid mark".to_string()),
+                        Some(letter) => state.set_mark(letter),
+                        None => {}
+                    }
+                    true
+                },
+            ),
+            Command::new(
+                vec![Key(KeyCode::Char('\''))],
+                "Go to mark, `'` goes to the position before the last jump".to_string(),
+                |state: &mut State| {
+                    let (found, message) = match read_mark(state, "goto mark: ") {
+                        Some('\'') => (state.jump_previous(), "No previous position"),
+                        Some(letter) => (state.jump_to_mark(letter), "Mark not set"),
+                        None => return true,
+                    };
+                    if !found {
+                        state.message = Some(message.to_string());
+                    }
+                    true
+                },
+            ),
+            Command::new(
+                vec![CommandType::ctrl('o')],
+                "Jump back".to_string(),
+                |state: &mut State| state.jump_back(),
+            ),
+            Command::new(
+                vec![Key(KeyCode::Tab), CommandType::ctrl('i')],
+                "Jump forward".to_string(),
+                |state: &mut State| state.jump_forward(),
+            ),
+        ])
+    }
+}
@@ -0,0 +1,195 @@
+use std::{
+    borrow::Cow,
+    fs::File,
+    io,
+    path::Path,
+    sync::{
+        mpsc::{channel, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+use memmap2::Mmap;
+
+use crate::ContentSource;
+
+/// Lines between the offsets kept by [`MmapSource`], other lines are found by scanning from them.
+const CHECKPOINT_INTERVAL: usize = 64;
+
+/// Bytes scanned by the indexing thread before sending what it found.
+const INDEX_CHUNK: usize = 4 * 1024 * 1024;
+
+/// Lines found in a chunk of the file.
+struct IndexUpdate {
+    /// Start offsets of every [`CHECKPOINT_INTERVAL`]th line.
+    checkpoints: Vec<usize>,
+    /// Total number of lines found.
+    line_count: usize,
+    /// Total number of bytes scanned.
+    scanned: usize,
+}
+
+/// [`ContentSource`] of a memory-mapped file.
+///
+/// The file is not read into memory, lines are indexed on a background thread and become
+/// available as the indexing goes, so large files are shown immediately.
+/// Invalid UTF-8 is replaced with `U+FFFD`.
+///
+/// The file should not be modified while it is shown.
+pub struct MmapSource {
+    mmap: Arc<Mmap>,
+    checkpoints: Vec<usize>,
+    line_count: usize,
+    scanned: usize,
+    receiver: Receiver<IndexUpdate>,
+    complete: bool,
+}
+
+impl MmapSource {
+    /// Map the file at `path` and start indexing its lines.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: The map is read-only, modifying the file while it is mapped is documented
+        // as unsupported.
+        let mmap = Arc::new(unsafe { Mmap::map(&file)? });
+
+        let (sender, receiver) = channel();
+        let thread_mmap = Arc::clone(&mmap);
+        thread::spawn(move || {
+            let bytes = &thread_mmap[..];
+            let mut line_count = 0;
+            for chunk_start in (0..bytes.len()).step_by(INDEX_CHUNK) {
+                let chunk_end = (chunk_start + INDEX_CHUNK).min(bytes.len());
+                let mut checkpoints = vec![];
+                let mut start = chunk_start;
+                // A line starts at the start of the file and after each `\n`,
+                // except the one at the end.
+                if chunk_start == 0 {
+                    checkpoints.push(0);
+                    line_count = 1;
+                }
+                for (i, &byte) in bytes[chunk_start..chunk_end].iter().enumerate() {
+                    start = chunk_start + i + 1;
+                    if byte == b'\n' && start < bytes.len() {
+                        if line_count % CHECKPOINT_INTERVAL == 0 {
+                            checkpoints.push(start);
+                        }
+                        line_count += 1;
+                    }
+                }
+                let update = IndexUpdate {
+                    checkpoints,
+                    line_count,
+                    scanned: start,
+                };
+                if sender.send(update).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            mmap,
+            checkpoints: vec![],
+            line_count: 0,
+            scanned: 0,
+            receiver,
+            complete: false,
+        })
+    }
+
+    /// Byte range of the line at `index`, without the line ending.
+    fn line_range(&self, index: usize) -> Option<(usize, usize)> {
+        if index >= self.line_count {
+            return None;
+        }
+        let bytes = &self.mmap[..];
+        let mut start = self.checkpoints[index / CHECKPOINT_INTERVAL];
+        for _ in 0..index % CHECKPOINT_INTERVAL {
+            start += next_newline(&bytes[start..]) + 1;
+        }
+        Some((start, start + next_newline(&bytes[start..])))
+    }
+}
+
+/// Position of the first `\n` in `bytes`, or its length.
+fn next_newline(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .unwrap_or(bytes.len())
+}
+
+fn decode(line: &[u8]) -> Cow<'_, str> {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line)
+}
+
+impl ContentSource for MmapSource {
+    fn poll(&mut self) -> bool {
+        let line_count = self.line_count;
+        while !self.complete {
+            match self.receiver.try_recv() {
+                Ok(update) => {
+                    self.checkpoints.extend(update.checkpoints);
+                    self.line_count = update.line_count;
+                    self.scanned = update.scanned;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.complete = true,
+            }
+        }
+        self.line_count != line_count
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn progress(&self) -> Option<f32> {
+        match self.mmap.len() {
+            0 => None,
+            len => Some(self.scanned as f32 / len as f32),
+        }
+    }
+
+    fn line_count(&self) -> usize {
+        self.line_count
+    }
+
+    fn line(&self, index: usize) -> Option<Cow<'_, str>> {
+        let (start, end) = self.line_range(index)?;
+        Some(decode(&self.mmap[start..end]))
+    }
+
+    fn lines_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        let offset = self
+            .line_range(start)
+            .map_or(self.mmap.len(), |(start, _)| start);
+        Box::new(
+            self.mmap[offset..]
+                .split(|&byte| byte == b'\n')
+                .take(self.line_count.saturating_sub(start))
+                .map(decode),
+        )
+    }
+
+    fn line_at_byte(&self, offset: usize) -> Option<usize> {
+        let checkpoint = self
+            .checkpoints
+            .partition_point(|&start| start <= offset)
+            .checked_sub(1)?;
+        let mut index = checkpoint * CHECKPOINT_INTERVAL;
+        let mut start = self.checkpoints[checkpoint];
+        while index < self.line_count {
+            let end = start + next_newline(&self.mmap[start..]);
+            if offset <= end.min(self.mmap.len() - 1) {
+                return Some(index);
+            }
+            index += 1;
+            start = end + 1;
+        }
+        None
+    }
+}
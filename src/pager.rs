@@ -0,0 +1,444 @@
+use std::{
+    io::{self, stdout, IsTerminal, Write},
+    panic,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, Once, PoisonError,
+    },
+    thread::{self, ThreadId},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    finish_with, init_with, run,
+    text::{self, join_spans, render, Expansion},
+    CommandList, ContentSource, CrosstermBackend, IntoContentSource, Line, State, StatusBar,
+    CONTENT_POLL_INTERVAL,
+};
+
+/// Number of [`Pager`]s alive, the panic hook restores the terminal only if there is any.
+static ACTIVE: AtomicUsize = AtomicUsize::new(0);
+/// Options that the terminal is set up with and the thread that set it up, for the panic hook.
+static ACTIVE_OPTIONS: Mutex<Option<(PagerOptions, ThreadId)>> = Mutex::new(None);
+/// Whether the panic hook restored the terminal, so the last [`Pager`] doesn't restore it again.
+static RESTORED: AtomicBool = AtomicBool::new(false);
+static PANIC_HOOK: Once = Once::new();
+
+/// How long [`PagerOptions::quit_if_one_screen`] waits for the content to complete, before
+/// paging it anyway.
+const ONE_SCREEN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Keeps the terminal set up for running [`State`]s.
+///
+/// Calls [`init_with`] on creation, or on the first [`Pager::run`] with
+/// [`PagerOptions::quit_if_one_screen`], and [`finish_with`] on drop, so the terminal is
+/// restored even if [`run`] returns an error. If the thread that set it up panics, e.g. in a
+/// command, the terminal is restored before the panic message is printed.
+///
+/// If stdout is not a terminal, the terminal is left as it is and the content is printed.
+///
+/// ```no_run
+/// # use pager_rs::{CommandList, Pager, State, StatusBar};
+/// let mut state = State::new(
+///     "Hello, world!".to_string(),
+///     StatusBar::new("Hello".to_string()),
+///     CommandList::default(),
+/// )?;
+/// Pager::new()?.run(&mut state)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Pager {
+    options: PagerOptions,
+    /// Whether [`init_with`] is called, it waits for the first run with
+    /// [`PagerOptions::quit_if_one_screen`].
+    initialized: bool,
+}
+
+/// Options of a [`Pager`].
+///
+/// ```no_run
+/// # use pager_rs::{CommandList, Pager, PagerOptions, State, StatusBar};
+/// let mut state = State::new(
+///     "Short output".to_string(),
+///     StatusBar::new("Output".to_string()),
+///     CommandList::default(),
+/// )?;
+/// // Like `less -FX`.
+/// let options = PagerOptions {
+///     quit_if_one_screen: true,
+///     alternate_screen: false,
+/// };
+/// Pager::with_options(options)?.run(&mut state)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PagerOptions {
+    /// Print the content and return without paging it, if it fits on one screen.
+    ///
+    /// Waits until the content is complete, or until it doesn't fit. Content that is not
+    /// complete after a second is paged.
+    pub quit_if_one_screen: bool,
+
+    /// Draw on the alternate screen, so the screen is restored after quitting.
+    ///
+    /// Otherwise the last screen stays in the scrollback.
+    pub alternate_screen: bool,
+}
+
+impl Default for PagerOptions {
+    fn default() -> Self {
+        Self {
+            quit_if_one_screen: false,
+            alternate_screen: true,
+        }
+    }
+}
+
+impl Pager {
+    /// Setup the terminal, see [`init`](crate::init).
+    pub fn new() -> std::io::Result<Self> {
+        Self::with_options(PagerOptions::default())
+    }
+
+    /// Setup the terminal with `options`, see [`init_with`].
+    ///
+    /// With [`PagerOptions::quit_if_one_screen`] the terminal is set up by the first
+    /// [`Pager::run`] that doesn't fit on one screen.
+    pub fn with_options(options: PagerOptions) -> std::io::Result<Self> {
+        PANIC_HOOK.call_once(|| {
+            let hook = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if panic_restores() {
+                    restore();
+                }
+                hook(info);
+            }));
+        });
+        let mut pager = Self {
+            options,
+            initialized: false,
+        };
+        if !options.quit_if_one_screen && stdout().is_terminal() {
+            pager.init()?;
+        }
+        Ok(pager)
+    }
+
+    /// Run `state` until it quits, see [`run`].
+    ///
+    /// With [`PagerOptions::quit_if_one_screen`], content that fits on one screen is printed
+    /// instead. If stdout is not a terminal, the content is printed as it is read.
+    pub fn run(&mut self, state: &mut State) -> std::io::Result<()> {
+        if !stdout().is_terminal() {
+            return stream(&mut *state.content);
+        }
+        // Filtered content is paged, since lines are filtered while it runs.
+        if self.options.quit_if_one_screen && !self.initialized && state.filter.is_none() {
+            let size = (state.size.0 as usize, state.text_height());
+            let expansion = state.expansion();
+            if let Some(lines) =
+                one_screen(&mut *state.content, size, expansion, ONE_SCREEN_TIMEOUT)
+            {
+                let mut out = stdout().lock();
+                for line in lines {
+                    writeln!(out, "{}", line)?;
+                }
+                return out.flush();
+            }
+        }
+        self.init()?;
+        run(state)
+    }
+
+    fn init(&mut self) -> io::Result<()> {
+        if !self.initialized {
+            init_with(&self.options)?;
+            activate(self.options);
+            self.initialized = true;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        if self.initialized && deactivate() {
+            let _ = CrosstermBackend::reset_raw_mode();
+            let _ = finish_with(&self.options);
+        }
+    }
+}
+
+/// Show `content` in a [`Pager`], or print it if stdout is not a terminal.
+///
+/// Apps can call it whether their output is shown or redirected, e.g. to a file or a pipe.
+///
+/// ```no_run
+/// # use pager_rs::{page, CommandList, ReaderSource, StatusBar};
+/// page(
+///     ReaderSource::stdin(),
+///     StatusBar::new("stdin".to_string()),
+///     CommandList::default(),
+/// )?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn page<C: IntoContentSource>(
+    content: C,
+    status_bar: StatusBar,
+    commands: CommandList,
+) -> io::Result<()> {
+    page_with(content, status_bar, commands, PagerOptions::default())
+}
+
+/// Show `content` in a [`Pager`] with `options`, or print it if stdout is not a terminal.
+///
+/// See: [`page`]
+pub fn page_with<C: IntoContentSource>(
+    content: C,
+    status_bar: StatusBar,
+    commands: CommandList,
+    options: PagerOptions,
+) -> io::Result<()> {
+    // Creating a `State` needs the size of the terminal.
+    if !stdout().is_terminal() {
+        return stream(&mut *content.into_content_source());
+    }
+    let mut state = State::new(content, status_bar, commands)?;
+    Pager::with_options(options)?.run(&mut state)
+}
+
+/// Write the lines of `content` to stdout as they are read, until it is complete.
+///
+/// Lines are written as they are, with their escape sequences.
+fn stream(content: &mut dyn ContentSource) -> io::Result<()> {
+    match write_lines(content, &mut stdout()) {
+        // The reader is gone, e.g. `| head`.
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+fn write_lines(content: &mut dyn ContentSource, out: &mut impl Write) -> io::Result<()> {
+    let mut written = 0;
+    loop {
+        content.poll();
+        // Checked before writing, so no line that is pulled with it is missed.
+        let complete = content.is_complete();
+        for line in content.lines_from(written) {
+            writeln!(out, "{}", line)?;
+            written += 1;
+        }
+        out.flush()?;
+        if complete {
+            return Ok(());
+        }
+        thread::sleep(CONTENT_POLL_INTERVAL);
+    }
+}
+
+/// Count a [`Pager`] that set up the terminal with `options` on the current thread.
+fn activate(options: PagerOptions) {
+    *ACTIVE_OPTIONS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some((options, thread::current().id()));
+    if ACTIVE.fetch_add(1, Ordering::SeqCst) == 0 {
+        RESTORED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Stop counting a [`Pager`], returns true if it is the last one and the terminal is not
+/// restored yet.
+fn deactivate() -> bool {
+    let active = ACTIVE.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+    active == Ok(1) && !RESTORED.swap(false, Ordering::SeqCst)
+}
+
+/// Returns true if the terminal should be restored for a panic on the current thread.
+///
+/// Only panics on the thread that set up the terminal restore it, e.g. not the ones of a reader
+/// thread that the pager keeps drawing after. It is restored once, [`Pager`]s that are dropped
+/// while unwinding leave it as it is.
+fn panic_restores() -> bool {
+    let owner = ACTIVE_OPTIONS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .map(|(_, thread)| thread);
+    ACTIVE.load(Ordering::SeqCst) > 0
+        && owner == Some(thread::current().id())
+        && !RESTORED.swap(true, Ordering::SeqCst)
+}
+
+/// Undo [`init_with`] with the active options and raw mode, ignoring errors.
+fn restore() {
+    let options = ACTIVE_OPTIONS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .map(|(options, _)| options)
+        .unwrap_or_default();
+    let _ = CrosstermBackend::reset_raw_mode();
+    let _ = finish_with(&options);
+}
+
+/// Get the lines of `content` as they are shown, if they fit on a screen of `(width, height)`
+/// rows.
+///
+/// Waits for the content until it is complete, until it doesn't fit, or for `timeout`, e.g. for
+/// a following source that never completes.
+fn one_screen(
+    content: &mut dyn ContentSource,
+    (width, height): (usize, usize),
+    expansion: Expansion,
+    timeout: Duration,
+) -> Option<Vec<String>> {
+    let start = Instant::now();
+    let width = width.max(1);
+    let (mut lines, mut rows) = (vec![], 0);
+    loop {
+        content.poll();
+        for spans in content.spans_from(lines.len()) {
+            let (text, styles) = join_spans(spans);
+            let expanded = expansion.expand(&text);
+            let mut styles = expanded.map_styles(styles);
+            styles.extend(expanded.special.iter().cloned());
+            rows += text::width(&expanded.text).div_ceil(width).max(1);
+            if rows > height {
+                return None;
+            }
+            lines.push(Line::new(render(&expanded.text, &[&styles], 0, usize::MAX)).to_ansi());
+        }
+        if content.is_complete() {
+            return Some(lines);
+        }
+        if start.elapsed() >= timeout {
+            return None;
+        }
+        thread::sleep(CONTENT_POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::channel;
+
+    use crossterm::style::{ContentStyle, Stylize};
+
+    use super::*;
+    use crate::{ChannelSource, ControlChars, TextSource};
+
+    fn expansion() -> Expansion {
+        Expansion {
+            tab_width: 4,
+            control_chars: ControlChars::Caret,
+            show_whitespace: false,
+            style: ContentStyle::new(),
+        }
+    }
+
+    fn lines(text: &str, size: (usize, usize)) -> Option<Vec<String>> {
+        let mut content = TextSource::new(text.to_string());
+        one_screen(&mut content, size, expansion(), ONE_SCREEN_TIMEOUT)
+    }
+
+    fn streamed(content: &mut dyn ContentSource) -> String {
+        let mut out = vec![];
+        write_lines(content, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn lines_are_streamed_as_they_are() {
+        let text = "a\x1b[31mb\x1b[0m\n\tc\n\n";
+        assert_eq!(streamed(&mut TextSource::new(text.to_string())), text);
+        assert_eq!(streamed(&mut TextSource::new(String::new())), "");
+    }
+
+    #[cfg(feature = "syntax-highlighting")]
+    #[test]
+    fn highlighted_source_streams_its_content() {
+        let text = "fn main() {\n    println!(\"\x1b[1mhi\x1b[0m\");\n}\n";
+        let mut content = crate::HighlightedSource::new(TextSource::new(text.to_string()), "rs");
+        assert_eq!(streamed(&mut content), text);
+        // The lines are highlighted when they are shown.
+        assert_ne!(content.spans_from(0).next().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn content_that_fits() {
+        assert_eq!(lines("a\tb\n\x01", (10, 2)).unwrap(), ["a   b", "^A"]);
+        assert_eq!(lines("", (10, 0)).unwrap(), Vec::<String>::new());
+        let red = format!("{}", "red".red());
+        assert_eq!(lines(&red, (10, 1)).unwrap(), [red]);
+    }
+
+    #[test]
+    fn content_that_does_not_fit() {
+        assert_eq!(lines("a\nb\nc", (10, 2)), None);
+        assert_eq!(lines("a", (10, 0)), None);
+    }
+
+    #[test]
+    fn long_lines_take_wrapped_rows() {
+        let text = "0123456789abcde\nx";
+        assert_eq!(lines(text, (10, 2)), None);
+        assert_eq!(lines(text, (10, 3)).unwrap().len(), 2);
+        // Tabs and wide characters count their columns.
+        assert_eq!(lines("\t\t\tx", (10, 1)), None);
+        assert_eq!(lines("日本語日本", (10, 1)).unwrap().len(), 1);
+        assert_eq!(lines("日本語日本語", (10, 1)), None);
+    }
+
+    #[test]
+    fn content_is_waited_for() {
+        let (sender, receiver) = channel();
+        let mut content = ChannelSource::new(receiver);
+        thread::spawn(move || {
+            sender.send("a".to_string()).unwrap();
+            thread::sleep(CONTENT_POLL_INTERVAL * 2);
+            sender.send("b".to_string()).unwrap();
+        });
+        let lines = one_screen(&mut content, (10, 2), expansion(), ONE_SCREEN_TIMEOUT);
+        assert_eq!(lines.unwrap(), ["a", "b"]);
+    }
+
+    #[test]
+    fn incomplete_content_times_out() {
+        let (sender, receiver) = channel();
+        let mut content = ChannelSource::new(receiver);
+        sender.send("a".to_string()).unwrap();
+
+        let start = Instant::now();
+        let lines = one_screen(&mut content, (10, 2), expansion(), ONE_SCREEN_TIMEOUT);
+        assert_eq!(lines, None);
+        assert!(start.elapsed() >= ONE_SCREEN_TIMEOUT);
+        assert!(start.elapsed() < ONE_SCREEN_TIMEOUT + CONTENT_POLL_INTERVAL * 5);
+        drop(sender);
+    }
+
+    /// The counts are global, so the panic hook and the drop order are tested in one test.
+    #[test]
+    fn panic_restores_once_on_the_owner_thread() {
+        let options = PagerOptions::default();
+
+        // A pager that is dropped without a panic restores the terminal.
+        activate(options);
+        assert!(deactivate());
+        assert!(!panic_restores());
+
+        // Panics on other threads, e.g. of a reader, leave the terminal.
+        activate(options);
+        let other = thread::spawn(panic_restores).join().unwrap();
+        assert!(!other);
+
+        // A panic on the owner thread restores it once, and the drop doesn't again.
+        activate(options);
+        assert!(panic_restores());
+        assert!(!panic_restores());
+        assert!(!deactivate());
+        assert!(!deactivate());
+
+        // The next pager restores the terminal again.
+        activate(options);
+        assert!(deactivate());
+        assert_eq!(ACTIVE.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,327 @@
+use crossterm::event::KeyCode;
+
+use crate::{
+    keys::KeyBinding,
+    search::search_command,
+    state::{or_goto_count, show_help},
+    Command, CommandList, CommandType, SearchDirection, State,
+};
+
+/// Movement command that repeats with the count prefix, like `5j`.
+fn movement<F>(cmd: Vec<CommandType>, desc: &str, func: F) -> Command
+where
+    F: FnMut(&mut State) -> bool + 'static,
+{
+    Command::repeated(cmd, desc.to_string(), func)
+}
+
+fn help(cmd: Vec<CommandType>) -> Command {
+    Command::new(cmd, "Toggles help text visiblity".to_string(), show_help)
+}
+
+fn quit(cmd: Vec<CommandType>) -> Command {
+    Command::new(cmd, "Quit".to_string(), |state: &mut State| {
+        state.quit();
+        false
+    })
+}
+
+impl CommandList {
+    /// Key bindings of `less`.
+    ///
+    /// Includes movement (`j/k`, `Space/b`, `d/u`, `e/y`, `Ctrl-F/B`, ...), search,
+    /// follow (`F`), `-N` to toggle line numbers and `-S` to toggle word wrap.
+    pub fn less() -> Self {
+        use CommandType::*;
+        Self::combine(vec![
+            Self(vec![
+                quit(vec![
+                    Key(KeyCode::Char('q')),
+                    Key(KeyCode::Char('Q')),
+                    CommandType::sequence("ZZ"),
+                    Colon("quit".to_string()),
+                ]),
+                help(vec![
+                    Key(KeyCode::Char('h')),
+                    Key(KeyCode::Char('H')),
+                    Colon("help".to_string()),
+                ]),
+                movement(
+                    vec![
+                        Key(KeyCode::Char('j')),
+                        Key(KeyCode::Char('e')),
+                        Key(KeyCode::Down),
+                        Key(KeyCode::Enter),
+                        CommandType::ctrl('n'),
+                        CommandType::ctrl('e'),
+                    ],
+                    "One line down",
+                    |state: &mut State| state.down(),
+                ),
+                movement(
+                    vec![
+                        Key(KeyCode::Char('k')),
+                        Key(KeyCode::Char('y')),
+                        Key(KeyCode::Up),
+                        CommandType::ctrl('p'),
+                        CommandType::ctrl('y'),
+                        CommandType::ctrl('k'),
+                    ],
+                    "One line up",
+                    |state: &mut State| state.up(),
+                ),
+                movement(
+                    vec![
+                        Key(KeyCode::Char(' ')),
+                        Key(KeyCode::Char('f')),
+                        Key(KeyCode::PageDown),
+                        CommandType::ctrl('f'),
+                        CommandType::ctrl('v'),
+                    ],
+                    "One page down",
+                    |state: &mut State| state.pgdown(),
+                ),
+                movement(
+                    vec![
+                        Key(KeyCode::Char('b')),
+                        Key(KeyCode::PageUp),
+                        CommandType::ctrl('b'),
+                        CommandType::alt('v'),
+                    ],
+                    "One page up",
+                    |state: &mut State| state.pgup(),
+                ),
+                movement(
+                    vec![Key(KeyCode::Char('d')), CommandType::ctrl('d')],
+                    "Half page down",
+                    |state: &mut State| state.half_pgdown(),
+                ),
+                movement(
+                    vec![Key(KeyCode::Char('u')), CommandType::ctrl('u')],
+                    "Half page up",
+                    |state: &mut State| state.half_pgup(),
+                ),
+                movement(
+                    vec![Key(KeyCode::Left)],
+                    "Scroll left",
+                    |state: &mut State| state.left(),
+                ),
+                movement(
+                    vec![Key(KeyCode::Right)],
+                    "Scroll right",
+                    |state: &mut State| state.right(),
+                ),
+                Command::with_args(
+                    vec![
+                        Key(KeyCode::Char('g')),
+                        Key(KeyCode::Char('<')),
+                        Key(KeyCode::Home),
+                        CommandType::alt('<'),
+                    ],
+                    "Go to start, or to line <n> with a count".to_string(),
+                    or_goto_count(|state: &mut State| state.home()),
+                ),
+                Command::with_args(
+                    vec![
+                        Key(KeyCode::Char('G')),
+                        Key(KeyCode::Char('>')),
+                        Key(KeyCode::End),
+                        CommandType::alt('>'),
+                    ],
+                    "Go to end, or to line <n> with a count".to_string(),
+                    or_goto_count(|state: &mut State| state.end()),
+                ),
+                Command::new(
+                    vec![CommandType::sequence("-N")],
+                    "Show/Hide line numbers".to_string(),
+                    |state: &mut State| {
+                        state.show_line_numbers = !state.show_line_numbers;
+                        true
+                    },
+                ),
+                Command::new(
+                    vec![CommandType::sequence("-S")],
+                    "Activate/Deactivate word wrap".to_string(),
+                    |state: &mut State| {
+                        state.word_wrap = !state.word_wrap;
+                        true
+                    },
+                ),
+            ]),
+            Self::goto(),
+            Self::search(),
+            Self::filter(),
+            Self::marks(),
+            Self::follow(),
+            Self::set(),
+        ])
+    }
+
+    /// Key bindings of `vi`.
+    ///
+    /// Includes movement (`h/j/k/l`, `gg/G`, `Ctrl-F/B`, `Ctrl-D/U`, `Ctrl-E/Y`), search
+    /// and follow (`F`). Line numbers and word wrap can be changed with `:set`.
+    pub fn vi() -> Self {
+        use CommandType::*;
+        Self::combine(vec![
+            Self(vec![
+                quit(vec![
+                    Key(KeyCode::Char('q')),
+                    CommandType::sequence("ZZ"),
+                    CommandType::sequence("ZQ"),
+                    Colon("quit".to_string()),
+                ]),
+                help(vec![Key(KeyCode::F(1)), Colon("help".to_string())]),
+                movement(
+                    vec![
+                        Key(KeyCode::Char('j')),
+                        Key(KeyCode::Down),
+                        Key(KeyCode::Enter),
+                        CommandType::ctrl('e'),
+                        CommandType::ctrl('n'),
+                    ],
+                    "One line down",
+                    |state: &mut State| state.down(),
+                ),
+                movement(
+                    vec![
+                        Key(KeyCode::Char('k')),
+                        Key(KeyCode::Up),
+                        CommandType::ctrl('y'),
+                        CommandType::ctrl('p'),
+                    ],
+                    "One line up",
+                    |state: &mut State| state.up(),
+                ),
+                movement(
+                    vec![Key(KeyCode::Char('h')), Key(KeyCode::Left)],
+                    "Scroll left",
+                    |state: &mut State| state.left(),
+                ),
+                movement(
+                    vec![Key(KeyCode::Char('l')), Key(KeyCode::Right)],
+                    "Scroll right",
+                    |state: &mut State| state.right(),
+                ),
+                movement(
+                    vec![
+                        Key(KeyCode::Char(' ')),
+                        Key(KeyCode::PageDown),
+                        CommandType::ctrl('f'),
+                    ],
+                    "One page down",
+                    |state: &mut State| state.pgdown(),
+                ),
+                movement(
+                    vec![Key(KeyCode::PageUp), CommandType::ctrl('b')],
+                    "One page up",
+                    |state: &mut State| state.pgup(),
+                ),
+                movement(
+                    vec![CommandType::ctrl('d')],
+                    "Half page down",
+                    |state: &mut State| state.half_pgdown(),
+                ),
+                movement(
+                    vec![CommandType::ctrl('u')],
+                    "Half page up",
+                    |state: &mut State| state.half_pgup(),
+                ),
+                Command::with_args(
+                    vec![CommandType::sequence("gg"), Key(KeyCode::Home)],
+                    "Go to start, or to line <n> with a count".to_string(),
+                    or_goto_count(|state: &mut State| state.home()),
+                ),
+                Command::with_args(
+                    vec![Key(KeyCode::Char('G')), Key(KeyCode::End)],
+                    "Go to end, or to line <n> with a count".to_string(),
+                    or_goto_count(|state: &mut State| state.end()),
+                ),
+            ]),
+            Self::goto(),
+            Self::search(),
+            Self::filter(),
+            Self::marks(),
+            Self::follow(),
+            Self::set(),
+        ])
+    }
+
+    /// Key bindings of Emacs.
+    ///
+    /// Includes movement (`Ctrl-N/P`, `Ctrl-V`/`Alt-v`, `Alt-<`/`Alt->`, `Ctrl-F/B`),
+    /// `Ctrl-S`/`Ctrl-R` search and `Ctrl-X Ctrl-C` to quit.
+    pub fn emacs() -> Self {
+        use CommandType::*;
+        Self::combine(vec![
+            Self(vec![
+                quit(vec![
+                    Key(KeyCode::Char('q')),
+                    Keys(vec![KeyBinding::ctrl('x'), KeyBinding::ctrl('c')]),
+                    Colon("quit".to_string()),
+                ]),
+                help(vec![Key(KeyCode::F(1)), Colon("help".to_string())]),
+                movement(
+                    vec![Key(KeyCode::Down), CommandType::ctrl('n')],
+                    "One line down",
+                    |state: &mut State| state.down(),
+                ),
+                movement(
+                    vec![Key(KeyCode::Up), CommandType::ctrl('p')],
+                    "One line up",
+                    |state: &mut State| state.up(),
+                ),
+                movement(
+                    vec![Key(KeyCode::Left), CommandType::ctrl('b')],
+                    "Scroll left",
+                    |state: &mut State| state.left(),
+                ),
+                movement(
+                    vec![Key(KeyCode::Right), CommandType::ctrl('f')],
+                    "Scroll right",
+                    |state: &mut State| state.right(),
+                ),
+                movement(
+                    vec![
+                        Key(KeyCode::PageDown),
+                        Key(KeyCode::Char(' ')),
+                        CommandType::ctrl('v'),
+                    ],
+                    "One page down",
+                    |state: &mut State| state.pgdown(),
+                ),
+                movement(
+                    vec![Key(KeyCode::PageUp), CommandType::alt('v')],
+                    "One page up",
+                    |state: &mut State| state.pgup(),
+                ),
+                Command::with_args(
+                    vec![Key(KeyCode::Home), CommandType::alt('<')],
+                    "Go to start, or to line <n> with a count".to_string(),
+                    or_goto_count(|state: &mut State| state.home()),
+                ),
+                Command::with_args(
+                    vec![Key(KeyCode::End), CommandType::alt('>')],
+                    "Go to end, or to line <n> with a count".to_string(),
+                    or_goto_count(|state: &mut State| state.end()),
+                ),
+                Command::new(
+                    vec![CommandType::ctrl('s')],
+                    "Search forward".to_string(),
+                    |state: &mut State| search_command(state, "/", SearchDirection::Forward),
+                ),
+                Command::new(
+                    vec![CommandType::ctrl('r')],
+                    "Search backward".to_string(),
+                    |state: &mut State| search_command(state, "?", SearchDirection::Backward),
+                ),
+            ]),
+            Self::goto(),
+            Self::search(),
+            Self::filter(),
+            Self::marks(),
+            Self::follow(),
+            Self::set(),
+        ])
+    }
+}
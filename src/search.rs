@@ -0,0 +1,490 @@
+use std::ops::Range;
+
+use crossterm::event::KeyCode;
+use regex::Regex;
+
+use crate::{
+    prompt_with, text, Command, CommandList, CommandType, ContentSource, ContentView, State,
+};
+
+/// Direction of a [`Search`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchDirection {
+    /// Towards the end of the content.
+    Forward,
+    /// Towards the start of the content.
+    Backward,
+}
+
+impl SearchDirection {
+    /// Get the opposite direction.
+    pub fn reverse(self) -> Self {
+        match self {
+            Self::Forward => Self::Backward,
+            Self::Backward => Self::Forward,
+        }
+    }
+}
+
+/// Lines that are searched for a match at once, while going backward or counting the matches.
+const SEARCH_CHUNK: usize = 10_000;
+
+/// Lines around the screen that are searched while a pattern is previewed.
+const PREVIEW_LINES: usize = 1_000;
+
+/// A match as `(line index, byte range in line)`.
+type Match = (usize, Range<usize>);
+
+/// A regex search over the content of a [`State`].
+///
+/// Matches are found when they are jumped to, and counted in chunks while the pager is idle.
+#[derive(Clone, Debug)]
+pub struct Search {
+    /// Compiled search pattern.
+    pub regex: Regex,
+
+    /// Direction the search was started with.
+    ///
+    /// `n` repeats the search in this direction, `N` in the opposite one.
+    pub direction: SearchDirection,
+
+    /// Matches in the counted lines, in content order.
+    matches: Vec<Match>,
+
+    /// Number of content lines that are counted.
+    searched_lines: usize,
+
+    /// True if every available line is counted.
+    counted: bool,
+
+    /// The match that was jumped to last.
+    current: Option<Match>,
+}
+
+impl Search {
+    /// Compile `pattern`, matches are found later in the content.
+    pub fn new(pattern: &str, direction: SearchDirection) -> Result<Self, regex::Error> {
+        Ok(Self {
+            regex: Regex::new(pattern)?,
+            direction,
+            matches: vec![],
+            searched_lines: 0,
+            counted: false,
+            current: None,
+        })
+    }
+
+    /// Count the matches in the next chunk of lines of `content` that are not counted yet.
+    ///
+    /// Returns true if any line is counted.
+    pub fn update(&mut self, content: &dyn ContentSource) -> bool {
+        let start = self.searched_lines;
+        for (index, line) in content.texts_from(start).take(SEARCH_CHUNK).enumerate() {
+            for range in self.line_matches(&line) {
+                self.matches.push((start + index, range));
+            }
+            self.searched_lines = start + index + 1;
+        }
+        self.counted = self.searched_lines >= content.line_count();
+        self.searched_lines > start
+    }
+
+    /// Returns true if the matches of every available line are counted.
+    pub fn is_counted(&self) -> bool {
+        self.counted
+    }
+
+    /// Number of matches counted so far, see [`Search::is_counted`].
+    pub fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    /// Index of the current match, starting from `0`, if it is counted.
+    pub fn current(&self) -> Option<usize> {
+        let (line, range) = self.current.as_ref()?;
+        let index = self
+            .matches
+            .binary_search_by_key(&(*line, range.start), |(line, range)| (*line, range.start));
+        index.ok()
+    }
+
+    /// Byte ranges of matches in given line.
+    ///
+    /// The line should not contain escape sequences.
+    pub(crate) fn line_matches(&self, line: &str) -> Vec<Range<usize>> {
+        self.regex
+            .find_iter(line)
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect()
+    }
+
+    /// Find the match to jump to from `line`, when going towards `direction`.
+    ///
+    /// Goes on from the current match while it is at `line`, otherwise searches from `line`.
+    /// Only `limit` lines after or before `line` are searched.
+    fn next_from(
+        &self,
+        content: &dyn ContentSource,
+        line: usize,
+        direction: SearchDirection,
+        limit: usize,
+    ) -> Option<Match> {
+        let current = self
+            .current
+            .as_ref()
+            .filter(|(current, _)| *current == line);
+        // Matches are looked for after or before this `(line, byte)` position.
+        let from = match (current, direction) {
+            (Some((line, range)), SearchDirection::Forward) => (*line, range.start + 1),
+            (Some((line, range)), SearchDirection::Backward) => (*line, range.start),
+            (None, _) => (line, 0),
+        };
+        let index = self
+            .matches
+            .partition_point(|(line, range)| (*line, range.start) < from);
+        match direction {
+            SearchDirection::Forward => {
+                let end = from.0.saturating_add(limit);
+                if let Some(found) = self.matches.get(index) {
+                    return Some(found.clone()).filter(|(line, _)| *line <= end);
+                }
+                let start = from.0.max(self.searched_lines);
+                content
+                    .texts_from(start)
+                    .take(end.saturating_add(1).saturating_sub(start))
+                    .enumerate()
+                    .find_map(|(index, text)| {
+                        let line = start + index;
+                        let mut ranges = self.line_matches(&text).into_iter();
+                        ranges
+                            .find(|range| (line, range.start) >= from)
+                            .map(|range| (line, range))
+                    })
+            }
+            SearchDirection::Backward => {
+                let stop = from.0.saturating_sub(limit);
+                // Lines after the counted ones are searched in chunks, from the last one.
+                let mut end = from.0 + 1;
+                while end > self.searched_lines.max(stop) {
+                    let start = end
+                        .saturating_sub(SEARCH_CHUNK)
+                        .max(self.searched_lines.max(stop));
+                    let found = content
+                        .texts_from(start)
+                        .take(end - start)
+                        .enumerate()
+                        .flat_map(|(index, text)| {
+                            let line = start + index;
+                            let ranges = self.line_matches(&text).into_iter();
+                            ranges.map(move |range| (line, range))
+                        })
+                        .filter(|(line, range)| (*line, range.start) < from)
+                        .last();
+                    if found.is_some() {
+                        return found;
+                    }
+                    end = start;
+                }
+                index
+                    .checked_sub(1)
+                    .map(|index| self.matches[index].clone())
+                    .filter(|(line, _)| *line >= stop)
+            }
+        }
+    }
+}
+
+impl<'a> State<'a> {
+    /// Start a new search and jump to the first match in `direction`.
+    ///
+    /// Returns `Ok(false)` if there is no match to jump to.
+    pub fn search(
+        &mut self,
+        pattern: &str,
+        direction: SearchDirection,
+    ) -> Result<bool, regex::Error> {
+        self.search = Some(Search::new(pattern, direction)?);
+        Ok(self.search_step(direction, usize::MAX))
+    }
+
+    /// Jump to the next match in direction of the current search.
+    pub fn next_match(&mut self) -> bool {
+        match &self.search {
+            Some(search) => self.search_step(search.direction, usize::MAX),
+            None => false,
+        }
+    }
+
+    /// Jump to the next match in reverse direction of the current search.
+    pub fn prev_match(&mut self) -> bool {
+        match &self.search {
+            Some(search) => self.search_step(search.direction.reverse(), usize::MAX),
+            None => false,
+        }
+    }
+
+    /// Clear the current search and its highlights.
+    pub fn clear_search(&mut self) -> bool {
+        self.search.take().is_some()
+    }
+
+    /// Get the current search.
+    pub fn get_search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    /// Count the matches of the current search in the next chunk of lines.
+    ///
+    /// Returns true if any line is counted.
+    pub(crate) fn count_matches(&mut self) -> bool {
+        let view = ContentView::new(&*self.content, self.filter.as_ref());
+        match &mut self.search {
+            Some(search) if !search.is_counted() => search.update(&view),
+            _ => false,
+        }
+    }
+
+    /// Jump to the next match towards `direction`, if it is at most `limit` lines away.
+    fn search_step(&mut self, direction: SearchDirection, limit: usize) -> bool {
+        let Some(search) = &self.search else {
+            return false;
+        };
+        let Some((line_index, range)) =
+            search.next_from(&self.view(), self.pos.1, direction, limit)
+        else {
+            self.message = Some("Pattern not found".to_string());
+            return false;
+        };
+        if let Some(search) = &mut self.search {
+            search.current = Some((line_index, range.clone()));
+        }
+
+        self.record_jump();
+        self.scroll_to(line_index);
+
+        let column = self
+            .view()
+            .texts_from(line_index)
+            .next()
+            .map(|line| {
+                let expanded = self.expansion().expand(&line);
+                text::width(&expanded.text[..expanded.offset(range.start)])
+            })
+            .unwrap_or(0);
+        let width = self.text_width();
+        if column < self.pos.0 || column >= self.pos.0 + width {
+            self.pos.0 = column.saturating_sub(width / 2);
+        }
+        true
+    }
+}
+
+/// Prompt for a pattern and search towards `direction`.
+///
+/// Matches are previewed while the pattern is typed.
+/// An empty pattern repeats the current search.
+pub(crate) fn search_command(state: &mut State, prefix: &str, direction: SearchDirection) -> bool {
+    let (pos, sub_row) = (state.pos, state.sub_row);
+    let previous = state.search.clone();
+    // Previews are not kept in the jump list.
+    let marks = state.marks.clone();
+
+    let pattern = prompt_with(state, prefix, |state, pattern| {
+        (state.pos, state.sub_row) = (pos, sub_row);
+        if pattern.is_empty() {
+            state.search = previous.clone();
+            return true;
+        }
+        // Keep the last preview while the pattern is incomplete.
+        let Ok(search) = Search::new(pattern, direction) else {
+            return false;
+        };
+        // Only the lines near the screen are searched, so that typing stays fast.
+        state.search = Some(search);
+        state.search_step(direction, PREVIEW_LINES);
+        state.message = None;
+        true
+    });
+    (state.pos, state.sub_row) = (pos, sub_row);
+    state.search = previous;
+    state.marks = marks;
+    state.message = None;
+
+    let pattern = match pattern {
+        Ok(Some(pattern)) => pattern,
+        Ok(None) => return true,
+        Err(err) => {
+            state.message = Some(err.to_string());
+            return true;
+        }
+    };
+    if pattern.is_empty() {
+        if let Some(search) = &mut state.search {
+            search.direction = direction;
+        }
+        state.next_match();
+        return true;
+    }
+    if let Err(err) = state.search(&pattern, direction) {
+        state.message = Some(
+            err.to_string()
+                .lines()
+                .last()
+                .unwrap_or_default()
+                .to_string(),
+        );
+    }
+    true
+}
+
+impl CommandList {
+    /// Default bundle of 'search' commands.
+    ///
+    /// Includes: `/`, `?`, `n`, `N` keys
+    pub fn search() -> Self {
+        use CommandType::*;
+        Self(vec![
+            Command::new(
+                vec![Key(KeyCode::Char('/'))],
+                "Search forward".to_string(),
+                |state: &mut State| search_command(state, "/", SearchDirection::Forward),
+            ),
+            Command::new(
+                vec![Key(KeyCode::Char('?'))],
+                "Search backward".to_string(),
+                |state: &mut State| search_command(state, "?", SearchDirection::Backward),
+            ),
+            Command::new(
+                vec![Key(KeyCode::Char('n'))],
+                "Next match".to_string(),
+                |state: &mut State| state.next_match(),
+            ),
+            Command::new(
+                vec![Key(KeyCode::Char('N'))],
+                "Previous match".to_string(),
+                |state: &mut State| state.prev_match(),
+            ),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TextSource;
+
+    const CONTENT: &str = "one\ntwo two\n\nthree two\n";
+
+    fn content() -> TextSource {
+        TextSource::from(CONTENT.to_string())
+    }
+
+    fn search(pattern: &str) -> Search {
+        Search::new(pattern, SearchDirection::Forward).unwrap()
+    }
+
+    #[test]
+    fn counts_matches_in_order() {
+        let mut search = search("two");
+        assert!(!search.is_counted());
+        assert!(search.update(&content()));
+        assert!(search.is_counted());
+        assert_eq!(search.match_count(), 3);
+        assert_eq!(search.matches, [(1, 0..3), (1, 4..7), (3, 6..9)]);
+        assert!(!search.update(&content()));
+    }
+
+    #[test]
+    fn counts_in_chunks() {
+        let content = TextSource::from("two\n".repeat(SEARCH_CHUNK + 1));
+        let mut search = search("two");
+        search.update(&content);
+        assert_eq!(search.match_count(), SEARCH_CHUNK);
+        assert!(!search.is_counted());
+        search.update(&content);
+        assert_eq!(search.match_count(), SEARCH_CHUNK + 1);
+        assert!(search.is_counted());
+    }
+
+    #[test]
+    fn empty_matches_are_skipped() {
+        let mut search = search("x*");
+        search.update(&content());
+        assert_eq!(search.match_count(), 0);
+        assert!(search.line_matches("abc").is_empty());
+    }
+
+    #[test]
+    fn invalid_pattern() {
+        assert!(Search::new("(", SearchDirection::Forward).is_err());
+    }
+
+    #[test]
+    fn first_match_from_line() {
+        let search = search("two");
+        let next = |line, direction| search.next_from(&content(), line, direction, usize::MAX);
+        assert_eq!(next(0, SearchDirection::Forward), Some((1, 0..3)));
+        assert_eq!(next(2, SearchDirection::Forward), Some((3, 6..9)));
+        assert_eq!(next(4, SearchDirection::Forward), None);
+        assert_eq!(next(3, SearchDirection::Backward), Some((1, 4..7)));
+        assert_eq!(next(0, SearchDirection::Backward), None);
+    }
+
+    #[test]
+    fn counted_and_uncounted_matches_are_the_same() {
+        let mut counted = search("two");
+        counted.update(&content());
+        for line in 0..5 {
+            for direction in [SearchDirection::Forward, SearchDirection::Backward] {
+                assert_eq!(
+                    counted.next_from(&content(), line, direction, usize::MAX),
+                    search("two").next_from(&content(), line, direction, usize::MAX),
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn steps_from_current_match() {
+        let mut search = search("two");
+        search.current = Some((1, 0..3));
+        let next = |search: &Search, line, direction| {
+            search.next_from(&content(), line, direction, usize::MAX)
+        };
+        assert_eq!(next(&search, 1, SearchDirection::Forward), Some((1, 4..7)));
+        assert_eq!(next(&search, 1, SearchDirection::Backward), None);
+        search.current = Some((1, 4..7));
+        assert_eq!(next(&search, 1, SearchDirection::Backward), Some((1, 0..3)));
+        assert_eq!(search.current(), None);
+        search.update(&content());
+        assert_eq!(search.current(), Some(1));
+    }
+
+    #[test]
+    fn searches_from_line_when_view_moved() {
+        let mut search = search("two");
+        search.current = Some((1, 0..3));
+        let next = |line, direction| search.next_from(&content(), line, direction, usize::MAX);
+        assert_eq!(next(2, SearchDirection::Forward), Some((3, 6..9)));
+        assert_eq!(next(4, SearchDirection::Backward), Some((3, 6..9)));
+    }
+
+    #[test]
+    fn limit_stops_the_search() {
+        let search = search("three");
+        assert_eq!(
+            search.next_from(&content(), 0, SearchDirection::Forward, 2),
+            None
+        );
+        assert_eq!(
+            search.next_from(&content(), 0, SearchDirection::Forward, 3),
+            Some((3, 0..5))
+        );
+        let search = Search::new("one", SearchDirection::Backward).unwrap();
+        assert_eq!(
+            search.next_from(&content(), 3, SearchDirection::Backward, 2),
+            None
+        );
+    }
+}
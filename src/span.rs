@@ -0,0 +1,246 @@
+use std::{borrow::Cow, fmt::Display};
+
+use crossterm::style::{ContentStyle, StyledContent};
+
+use crate::{text::render_segment, ContentSource, IntoContentSource};
+
+/// Text that is printed with the same style, part of a [`Line`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Span {
+    /// The text.
+    pub text: String,
+    /// Style of the text.
+    pub style: ContentStyle,
+}
+
+impl Span {
+    /// Create a [`Span`] of `text` with `style`.
+    pub fn new(text: impl Into<String>, style: ContentStyle) -> Self {
+        Self {
+            text: text.into(),
+            style,
+        }
+    }
+
+    /// Create a [`Span`] of `text` without a style.
+    pub fn raw(text: impl Into<String>) -> Self {
+        Self::new(text, ContentStyle::new())
+    }
+}
+
+impl From<String> for Span {
+    fn from(text: String) -> Self {
+        Self::raw(text)
+    }
+}
+
+impl From<&str> for Span {
+    fn from(text: &str) -> Self {
+        Self::raw(text)
+    }
+}
+
+/// A [`Span`] from a styled text, e.g. `"error".red().bold()`.
+impl<D: Display> From<StyledContent<D>> for Span {
+    fn from(content: StyledContent<D>) -> Self {
+        Self::new(content.content().to_string(), *content.style())
+    }
+}
+
+/// Line of styled content.
+///
+/// A list of lines can be shown by a [`State`](crate::State) like a plain [`String`]:
+///
+/// ```
+/// # use std::{cell::RefCell, rc::Rc};
+/// # use crossterm::style::{Color, Stylize};
+/// # use pager_rs::{run, CommandList, Line, State, StatusBar, TestBackend};
+/// let lines = vec![
+///     Line::from(vec!["12:00 ".dim().into(), "error".red().into()]),
+///     Line::from("plain line"),
+/// ];
+///
+/// let backend = Rc::new(RefCell::new(TestBackend::new(20, 3)));
+/// backend.borrow_mut().push_keys("q");
+/// let mut state = State::with_backend(
+///     lines,
+///     StatusBar::new("Log".to_string()),
+///     CommandList::default(),
+///     backend.clone(),
+/// )?;
+/// state.show_line_numbers = false;
+/// run(&mut state)?;
+///
+/// let backend = backend.borrow();
+/// assert_eq!(backend.screen()[0], "12:00 error");
+/// assert_eq!(backend.cell(6, 0).unwrap().style.foreground_color, Some(Color::Red));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Line {
+    /// Parts of the line.
+    ///
+    /// Newlines in the spans start new lines.
+    pub spans: Vec<Span>,
+}
+
+impl Line {
+    /// Create a [`Line`] of `spans`.
+    pub fn new(spans: Vec<Span>) -> Self {
+        Self { spans }
+    }
+
+    /// Render the line as text with SGR escape sequences.
+    pub fn to_ansi(&self) -> String {
+        self.spans
+            .iter()
+            .map(|span| {
+                // Style each line of the span, so the style doesn't end at a newline.
+                span.text
+                    .split('\n')
+                    .map(|text| render_segment(text, span.style))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            })
+            .collect()
+    }
+}
+
+impl From<Vec<Span>> for Line {
+    fn from(spans: Vec<Span>) -> Self {
+        Self::new(spans)
+    }
+}
+
+impl<T: Into<Span>> From<T> for Line {
+    fn from(span: T) -> Self {
+        Self::new(vec![span.into()])
+    }
+}
+
+/// [`ContentSource`] of styled [`Line`]s.
+///
+/// The spans are kept as they are, so their text is shown as is, even if it contains escape
+/// sequences. Lines that are returned as text are rendered with [`Line::to_ansi`].
+#[derive(Clone, Debug, Default)]
+pub struct LineSource {
+    lines: Vec<Vec<Span>>,
+}
+
+impl LineSource {
+    /// Create a [`LineSource`] of `lines`, newlines in their spans start new lines.
+    pub fn new(lines: Vec<Line>) -> Self {
+        lines.into_iter().collect()
+    }
+}
+
+impl<L: Into<Line>> FromIterator<L> for LineSource {
+    fn from_iter<I: IntoIterator<Item = L>>(lines: I) -> Self {
+        let mut source = Self::default();
+        for line in lines {
+            let mut spans = vec![];
+            for span in line.into().spans {
+                let mut parts = span.text.split('\n');
+                if let Some(text) = parts.next() {
+                    spans.push(Span::new(text, span.style));
+                }
+                for text in parts {
+                    source.lines.push(std::mem::take(&mut spans));
+                    spans.push(Span::new(text, span.style));
+                }
+            }
+            source.lines.push(spans);
+        }
+        source
+    }
+}
+
+impl ContentSource for LineSource {
+    fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    fn line(&self, index: usize) -> Option<Cow<'_, str>> {
+        let spans = self.lines.get(index)?;
+        Some(Cow::Owned(Line::new(spans.clone()).to_ansi()))
+    }
+
+    fn spans_from(&self, start: usize) -> Box<dyn Iterator<Item = Vec<Span>> + '_> {
+        Box::new(self.lines[start.min(self.lines.len())..].iter().cloned())
+    }
+
+    fn texts_from(&self, start: usize) -> Box<dyn Iterator<Item = Cow<'_, str>> + '_> {
+        Box::new(
+            self.lines[start.min(self.lines.len())..]
+                .iter()
+                .map(|spans| match &spans[..] {
+                    [span] => Cow::Borrowed(span.text.as_str()),
+                    spans => Cow::Owned(spans.iter().map(|span| span.text.as_str()).collect()),
+                }),
+        )
+    }
+
+    /// Offsets are counted in the text of the spans, with a one byte line ending.
+    fn line_at_byte(&self, offset: usize) -> Option<usize> {
+        let mut end = 0;
+        for (index, line) in self.texts_from(0).enumerate() {
+            end += line.len() + 1;
+            if offset < end {
+                return Some(index);
+            }
+        }
+        None
+    }
+}
+
+impl IntoContentSource for Vec<Line> {
+    fn into_content_source(self) -> Box<dyn ContentSource> {
+        Box::new(LineSource::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::Stylize;
+
+    use super::*;
+
+    #[test]
+    fn newlines_split_lines_and_keep_styles() {
+        let source: LineSource = [
+            Line::from(vec!["a\nb".red().into(), "c".into()]),
+            Line::from("d"),
+        ]
+        .into_iter()
+        .collect();
+        let lines: Vec<Vec<Span>> = source.spans_from(0).collect();
+        assert_eq!(
+            lines,
+            [
+                vec![Span::from("a".red())],
+                vec![Span::from("b".red()), Span::raw("c")],
+                vec![Span::raw("d")],
+            ]
+        );
+    }
+
+    #[test]
+    fn texts_and_byte_offsets() {
+        let source = LineSource::new(vec![
+            Line::from(vec!["ab".bold().into(), "c".into()]),
+            "d".into(),
+        ]);
+        let texts: Vec<Cow<str>> = source.texts_from(0).collect();
+        assert_eq!(texts, ["abc", "d"]);
+        assert_eq!(source.line_at_byte(3), Some(0));
+        assert_eq!(source.line_at_byte(4), Some(1));
+        assert_eq!(source.line_at_byte(6), None);
+    }
+
+    #[test]
+    fn to_ansi_styles_each_line() {
+        let line = Line::from("a\nb".red());
+        assert_eq!(line.to_ansi(), format!("{}\n{}", "a".red(), "b".red()));
+        assert_eq!(Line::from("plain").to_ansi(), "plain");
+    }
+}
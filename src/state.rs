@@ -1,20 +1,53 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
+
 use crossterm::{
     event::KeyCode,
     style::{Attribute, Color, ContentStyle, Stylize},
-    terminal,
 };
 
-use crate::{run, status_bar::StatusBar, StatusBarLayout, StatusBarLayoutItem};
+use crate::{
+    complete_options,
+    keys::{sequence_name, PendingKeys},
+    run,
+    status_bar::StatusBar,
+    text::{
+        self, join_spans, render, shift_styles, slice_styles, wrapped_part, Expansion, StyleRanges,
+    },
+    wrap::WrapCache,
+    Backend, CommandArgs, CommandResult, ContentSource, ContentView, CrosstermBackend, Filter,
+    IntoContentSource, KeyBinding, Line, Marks, Search, Span, StatusBarLayout, StatusBarLayoutItem,
+};
 
 /// Type of [`Command`].
 #[derive(Clone, PartialEq)]
 pub enum CommandType {
     /// Waits for `:` key and then the command input, until Enter is pressed.
+    ///
+    /// The input is the name of the command, optionally followed by arguments.
+    ///
+    /// See: [`State::execute_colon`]
     Colon(String),
-    /// Waits for key input.
+    /// Waits for key input without `Ctrl` or `Alt`.
     Key(KeyCode),
+    /// Waits for a sequence of keys with modifiers, like `gg` or `Ctrl-f`.
+    ///
+    /// See: [`CommandType::sequence`], [`CommandType::ctrl`], [`CommandType::alt`]
+    Keys(Vec<KeyBinding>),
 }
 
+/// Function of a [`Command`].
+///
+/// It is shared between the clones of a [`Command`], so it can keep its own state.
+pub type CommandFn = Rc<RefCell<dyn FnMut(&mut State, &CommandArgs) -> CommandResult>>;
+
+/// Argument completer of a [`Command`].
+///
+/// Called with the index of the argument that is completed and its text typed so far,
+/// returns the completion candidates.
+///
+/// See: [`complete_options`](crate::complete_options), [`complete_path`](crate::complete_path)
+pub type CompleterFn = Rc<dyn Fn(&State, usize, &str) -> Vec<String>>;
+
 /// Command definition
 #[derive(Clone)]
 pub struct Command {
@@ -23,7 +56,79 @@ pub struct Command {
     /// Description of the command, can be seen in help text.
     pub desc: String,
     /// The function that runs when command executed.
-    pub func: &'static dyn Fn(&mut State) -> bool,
+    ///
+    /// See: [`CommandResult`]
+    pub func: CommandFn,
+    /// Completes the arguments of the command in the `:` prompt.
+    pub completer: Option<CompleterFn>,
+}
+
+impl Command {
+    /// Create a [`Command`] that takes no arguments.
+    ///
+    /// `func` returns true if the screen should be redrawn.
+    /// It can capture and modify values, e.g. a channel or a path from arguments.
+    pub fn new<F>(cmd: Vec<CommandType>, desc: String, mut func: F) -> Self
+    where
+        F: FnMut(&mut State) -> bool + 'static,
+    {
+        Self::with_args(cmd, desc, move |state, args| {
+            args.expect_len(0, 0)?;
+            Ok(func(state))
+        })
+    }
+
+    /// Create a [`Command`] that runs `func` as many times as [`CommandArgs::count`], like `5j`.
+    ///
+    /// Returns true if any of the runs returned true.
+    pub fn repeated<F>(cmd: Vec<CommandType>, desc: String, mut func: F) -> Self
+    where
+        F: FnMut(&mut State) -> bool + 'static,
+    {
+        Self::with_args(cmd, desc, move |state, args| {
+            args.expect_len(0, 0)?;
+            let mut flush = false;
+            for _ in 0..args.count.unwrap_or(1) {
+                flush |= func(state);
+            }
+            Ok(flush)
+        })
+    }
+
+    /// Create a [`Command`] that takes arguments, like `:goto 120`.
+    pub fn with_args<F>(cmd: Vec<CommandType>, desc: String, func: F) -> Self
+    where
+        F: FnMut(&mut State, &CommandArgs) -> CommandResult + 'static,
+    {
+        Self {
+            cmd,
+            desc,
+            func: Rc::new(RefCell::new(func)),
+            completer: None,
+        }
+    }
+
+    /// Set the argument completer of the command.
+    ///
+    /// See: [`CompleterFn`]
+    pub fn with_completer<F>(mut self, completer: F) -> Self
+    where
+        F: Fn(&State, usize, &str) -> Vec<String> + 'static,
+    {
+        self.completer = Some(Rc::new(completer));
+        self
+    }
+
+    /// Execute the command on `state` with `args`.
+    ///
+    /// Returns `Ok(false)` without executing if the command is already running,
+    /// e.g. when it is called again from a nested [`run`].
+    pub fn execute(&self, state: &mut State, args: &CommandArgs) -> CommandResult {
+        match self.func.try_borrow_mut() {
+            Ok(mut func) => func(state, args),
+            Err(_) => Ok(false),
+        }
+    }
 }
 
 /// Container of list of commands.
@@ -51,14 +156,14 @@ impl CommandList {
     /// Default 'quit' command
     pub fn quit() -> Self {
         use CommandType::*;
-        Self(vec![Command {
-            cmd: vec![Key(KeyCode::Char('q')), Colon("quit".to_string())],
-            desc: "Quit".to_string(),
-            func: &|state: &mut State| {
+        Self(vec![Command::new(
+            vec![Key(KeyCode::Char('q')), Colon("quit".to_string())],
+            "Quit".to_string(),
+            |state: &mut State| {
                 state.quit();
                 false
             },
-        }])
+        )])
     }
 
     /// Default bundle of 'navigation' commands.
@@ -67,112 +172,172 @@ impl CommandList {
     pub fn navigation() -> Self {
         use CommandType::*;
         Self(vec![
-            Command {
-                cmd: vec![Key(KeyCode::Up)],
-                desc: "Cursor up".to_string(),
-                func: &|state: &mut State| state.up(),
-            },
-            Command {
-                cmd: vec![Key(KeyCode::Down)],
-                desc: "Cursor down".to_string(),
-                func: &|state: &mut State| state.down(),
-            },
-            Command {
-                cmd: vec![Key(KeyCode::Left)],
-                desc: "Cursor left".to_string(),
-                func: &|state: &mut State| state.left(),
-            },
-            Command {
-                cmd: vec![Key(KeyCode::Right)],
-                desc: "Cursor right".to_string(),
-                func: &|state: &mut State| state.right(),
-            },
-            Command {
-                cmd: vec![Key(KeyCode::Home), Key(KeyCode::Char('g'))],
-                desc: "Go to start".to_string(),
-                func: &|state: &mut State| state.home(),
-            },
-            Command {
-                cmd: vec![Key(KeyCode::End), Key(KeyCode::Char('G'))],
-                desc: "Go to end".to_string(),
-                func: &|state: &mut State| state.end(),
-            },
-            Command {
-                cmd: vec![Key(KeyCode::PageUp)],
-                desc: "One page up".to_string(),
-                func: &|state: &mut State| state.pgup(),
-            },
-            Command {
-                cmd: vec![Key(KeyCode::PageDown)],
-                desc: "One page down".to_string(),
-                func: &|state: &mut State| state.pgdown(),
-            },
+            Command::repeated(
+                vec![Key(KeyCode::Up)],
+                "Cursor up".to_string(),
+                |state: &mut State| state.up(),
+            ),
+            Command::repeated(
+                vec![Key(KeyCode::Down)],
+                "Cursor down".to_string(),
+                |state: &mut State| state.down(),
+            ),
+            Command::repeated(
+                vec![Key(KeyCode::Left)],
+                "Cursor left".to_string(),
+                |state: &mut State| state.left(),
+            ),
+            Command::repeated(
+                vec![Key(KeyCode::Right)],
+                "Cursor right".to_string(),
+                |state: &mut State| state.right(),
+            ),
+            Command::with_args(
+                vec![Key(KeyCode::Home), Key(KeyCode::Char('g'))],
+                "Go to start, or to line <n> with a count".to_string(),
+                or_goto_count(|state: &mut State| state.home()),
+            ),
+            Command::with_args(
+                vec![Key(KeyCode::End), Key(KeyCode::Char('G'))],
+                "Go to end, or to line <n> with a count".to_string(),
+                or_goto_count(|state: &mut State| state.end()),
+            ),
+            Command::repeated(
+                vec![Key(KeyCode::PageUp)],
+                "One page up".to_string(),
+                |state: &mut State| state.pgup(),
+            ),
+            Command::repeated(
+                vec![Key(KeyCode::PageDown)],
+                "One page down".to_string(),
+                |state: &mut State| state.pgdown(),
+            ),
+        ])
+    }
+
+    /// Default bundle of 'goto' commands.
+    ///
+    /// Includes: `%` key with a count, `:goto <line>` (also `:<line>`) and `:byte <offset>`
+    pub fn goto() -> Self {
+        use CommandType::*;
+        Self(vec![
+            Command::with_args(
+                vec![Key(KeyCode::Char('%'))],
+                "Go to <n> percent of the content, like `50%`".to_string(),
+                |state: &mut State, args: &CommandArgs| {
+                    args.expect_len(0, 0)?;
+                    let percent = args.count.ok_or("needs a count, like `50%`")?;
+                    Ok(state.goto_percent(percent))
+                },
+            ),
+            Command::with_args(
+                vec![Colon("goto".to_string())],
+                "Go to a line, `:<line>` works too".to_string(),
+                |state: &mut State, args: &CommandArgs| {
+                    args.expect_len(1, 1)?;
+                    Ok(state.goto_line(args.parse(0)?))
+                },
+            ),
+            Command::with_args(
+                vec![Colon("byte".to_string())],
+                "Go to the line that contains a byte offset".to_string(),
+                |state: &mut State, args: &CommandArgs| {
+                    args.expect_len(1, 1)?;
+                    match state.goto_byte(args.parse(0)?) {
+                        true => Ok(true),
+                        false => Err("offset is after the end".to_string()),
+                    }
+                },
+            ),
         ])
     }
 
     /// Default 'help' command
     pub fn help() -> Self {
         use CommandType::*;
-        Self(vec![Command {
-            cmd: vec![Key(KeyCode::Char('h')), Colon("help".to_string())],
-            desc: "Toggles help text visiblity".to_string(),
-            func: &|state: &mut State| {
-                let theme = ContentStyle::new()
-                    .with(Color::Black)
-                    .on(Color::White)
-                    .attribute(Attribute::Bold);
-                let commands =
-                    CommandList::combine(vec![CommandList::quit(), CommandList::navigation()]);
-
-                let mut help = State {
-                    pos: (0, 0),
-                    size: state.size,
-                    content: state.get_help_text(),
-                    status_bar: StatusBar {
-                        line_layouts: vec![StatusBarLayout {
-                            left: vec![StatusBarLayoutItem::Text("Quit (q)".to_owned())],
-                            right: vec![],
-                        }],
-                        title: "Help text".to_owned(),
-                        theme,
-                    },
-                    commands,
-                    running: true,
-                    show_line_numbers: false,
-                    word_wrap: false,
-                    word_wrap_option: textwrap::Options::new(0),
-                };
-                run(&mut help).unwrap();
-                true
-            },
-        }])
+        Self(vec![Command::new(
+            vec![Key(KeyCode::Char('h')), Colon("help".to_string())],
+            "Toggles help text visiblity".to_string(),
+            show_help,
+        )])
+    }
+
+    /// Default 'follow' command
+    pub fn follow() -> Self {
+        use CommandType::*;
+        Self(vec![Command::new(
+            vec![Key(KeyCode::Char('F'))],
+            "Follow the end of content as it grows, until a key is pressed".to_string(),
+            |state: &mut State| state.follow(),
+        )])
     }
 
     /// Default 'toggle line numbers' command
     pub fn toggle_line_numbers() -> Self {
         use CommandType::*;
-        Self(vec![Command {
-            cmd: vec![Key(KeyCode::Char('l'))],
-            desc: "Show/Hide line numbers".to_string(),
-            func: &|state: &mut State| {
+        Self(vec![Command::new(
+            vec![Key(KeyCode::Char('l'))],
+            "Show/Hide line numbers".to_string(),
+            |state: &mut State| {
                 state.show_line_numbers = !state.show_line_numbers;
                 true
             },
-        }])
+        )])
+    }
+
+    /// Default 'set' command
+    ///
+    /// `:set <option>...` where option is one of `wrap`, `nowrap`, `number`, `nonumber`,
+    /// `list`, `nolist` or `tabstop=<n>`.
+    pub fn set() -> Self {
+        use CommandType::*;
+        Self(vec![Command::with_args(
+            vec![Colon("set".to_string())],
+            "Set options: wrap, nowrap, number, nonumber, list, nolist, tabstop=<n>".to_string(),
+            |state: &mut State, args: &CommandArgs| {
+                args.expect_len(1, usize::MAX)?;
+                for option in &args.args {
+                    match option.as_str() {
+                        "wrap" => state.word_wrap = true,
+                        "nowrap" => state.word_wrap = false,
+                        "number" => state.show_line_numbers = true,
+                        "nonumber" => state.show_line_numbers = false,
+                        "list" => state.show_whitespace = true,
+                        "nolist" => state.show_whitespace = false,
+                        option => match option.strip_prefix("tabstop=") {
+                            Some(width) => match width.parse() {
+                                Ok(width) if width > 0 => state.tab_width = width,
+                                _ => return Err(format!("invalid tab width '{}'", width)),
+                            },
+                            None => return Err(format!("unknown option '{}'", option)),
+                        },
+                    }
+                }
+                Ok(true)
+            },
+        )
+        .with_completer(|_, _, partial| {
+            complete_options(
+                &[
+                    "wrap", "nowrap", "number", "nonumber", "list", "nolist", "tabstop=",
+                ],
+                partial,
+            )
+        })])
     }
 
     /// Default 'toggle word wrap' command
     pub fn toggle_word_wrap() -> Self {
         use CommandType::*;
-        Self(vec![Command {
-            cmd: vec![Key(KeyCode::Char('w'))],
-            desc: "Activate/Deactivate word wrap".to_string(),
-            func: &|state: &mut State| {
+        Self(vec![Command::new(
+            vec![Key(KeyCode::Char('w'))],
+            "Activate/Deactivate word wrap".to_string(),
+            |state: &mut State| {
                 state.word_wrap = !state.word_wrap;
 
                 true
             },
-        }])
+        )])
     }
 }
 
@@ -181,18 +346,103 @@ impl Default for CommandList {
         Self::combine(vec![
             Self::quit(),
             Self::navigation(),
+            Self::goto(),
             Self::help(),
             Self::toggle_line_numbers(),
             Self::toggle_word_wrap(),
+            Self::search(),
+            Self::filter(),
+            Self::marks(),
+            Self::follow(),
+            Self::set(),
         ])
     }
 }
 
+/// Command function that goes to the line of [`CommandArgs::count`] if there is one,
+/// and runs `func` otherwise.
+pub(crate) fn or_goto_count<F>(func: F) -> impl FnMut(&mut State, &CommandArgs) -> CommandResult
+where
+    F: Fn(&mut State) -> bool + 'static,
+{
+    move |state, args| {
+        args.expect_len(0, 0)?;
+        Ok(match args.count {
+            Some(number) => state.goto_line(number),
+            None => func(state),
+        })
+    }
+}
+
+/// Show the help text of `state` in a nested [`State`].
+pub(crate) fn show_help(state: &mut State) -> bool {
+    let theme = ContentStyle::new()
+        .with(Color::Black)
+        .on(Color::White)
+        .attribute(Attribute::Bold);
+    let commands = CommandList::combine(vec![CommandList::quit(), CommandList::navigation()]);
+
+    let status_bar = StatusBar {
+        line_layouts: vec![StatusBarLayout {
+            left: vec![StatusBarLayoutItem::Text("Quit (q)".to_owned())],
+            right: vec![],
+        }],
+        title: "Help text".to_owned(),
+        theme,
+    };
+
+    let result = State::with_backend(
+        state.get_help_text(),
+        status_bar,
+        commands,
+        state.backend.clone(),
+    )
+    .and_then(|mut help| {
+        help.show_line_numbers = false;
+        help.search_theme = state.search_theme;
+        help.tab_width = state.tab_width;
+        help.control_chars = state.control_chars;
+        help.special_chars_theme = state.special_chars_theme;
+        help.key_timeout = state.key_timeout;
+        run(&mut help)
+    });
+    if let Err(err) = result {
+        state.message = Some(err.to_string());
+    }
+    true
+}
+
+/// How control characters in the content are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlChars {
+    /// Caret notation, e.g. `^A` for `\x01` and `^?` for `\x7f`.
+    #[default]
+    Caret,
+    /// Control pictures, e.g. `␁` for `\x01` and `␡` for `\x7f`.
+    Picture,
+}
+
+impl ControlChars {
+    /// Text shown for the control character `c`.
+    pub(crate) fn replacement(self, c: char) -> String {
+        match (self, c as u32) {
+            (Self::Caret, code @ 0..=0x1f) => format!("^{}", char::from(code as u8 + b'@')),
+            (Self::Caret, 0x7f) => "^?".to_string(),
+            (Self::Picture, code @ 0..=0x1f) => char::from_u32(0x2400 + code)
+                .unwrap_or('\u{fffd}')
+                .to_string(),
+            (Self::Picture, 0x7f) => "␡".to_string(),
+            // C1 controls have no notation.
+            _ => '\u{fffd}'.to_string(),
+        }
+    }
+}
+
 /// State that can be ran with `pager_rs::run`
 pub struct State<'a> {
     /// Cursor position in content.
     ///
-    /// `(x, y)`
+    /// `(x, y)`, where `x` is the first shown column and `y` the index of the line at the top.
     pub pos: (usize, usize),
 
     /// Size of terminal screen.
@@ -201,7 +451,7 @@ pub struct State<'a> {
     pub size: (u16, u16),
 
     /// Content to show.
-    pub content: String,
+    pub content: Box<dyn ContentSource>,
 
     /// status bar at the bottom.
     pub status_bar: StatusBar,
@@ -221,28 +471,124 @@ pub struct State<'a> {
     ///
     /// The `width` is not important since it will be replaced by terminal screen width when rendering text.
     pub word_wrap_option: textwrap::Options<'a>,
+
+    pub(crate) search: Option<Search>,
+
+    pub(crate) filter: Option<Filter>,
+
+    pub(crate) marks: Marks,
+
+    /// Style of search matches.
+    pub search_theme: ContentStyle,
+
+    /// Message shown in place of the last [`StatusBar`] line until the next input.
+    pub message: Option<String>,
+
+    /// Number of columns between tab stops.
+    pub tab_width: usize,
+
+    /// How control characters are shown.
+    pub control_chars: ControlChars,
+
+    /// Show tabs, trailing spaces and line ends with visible characters.
+    pub show_whitespace: bool,
+
+    /// Style of control characters and the characters shown for whitespace.
+    pub special_chars_theme: ContentStyle,
+
+    pub(crate) following: bool,
+
+    /// Prompt histories by prompt prefix.
+    pub(crate) history: HashMap<String, Vec<String>>,
+
+    pub(crate) pending_keys: PendingKeys,
+
+    /// How long to wait for the next key of a sequence, when a shorter sequence matches too.
+    pub key_timeout: Duration,
+
+    /// Row of the line at [`State::pos`] that is shown at the top, when it is word-wrapped.
+    pub(crate) sub_row: usize,
+
+    /// Rows of wrapped lines, see [`State::line_rows`].
+    pub(crate) wrap_cache: RefCell<WrapCache>,
+
+    /// Screen to draw on and read events from.
+    ///
+    /// Nested [`State`]s can share it, e.g. `State::with_backend(.., state.backend.clone())`.
+    pub backend: Rc<RefCell<dyn Backend>>,
 }
 
 impl<'a> State<'a> {
-    /// Create new [`State`]
-    pub fn new(
-        content: String,
+    /// Create new [`State`] that runs on the terminal.
+    pub fn new<C: IntoContentSource>(
+        content: C,
         status_bar: StatusBar,
         commands: CommandList,
     ) -> std::io::Result<Self> {
+        Self::with_backend(
+            content,
+            status_bar,
+            commands,
+            Rc::new(RefCell::new(CrosstermBackend)),
+        )
+    }
+
+    /// Create new [`State`] that runs on `backend`, e.g. a [`TestBackend`](crate::TestBackend).
+    pub fn with_backend<C: IntoContentSource>(
+        content: C,
+        status_bar: StatusBar,
+        commands: CommandList,
+        backend: Rc<RefCell<dyn Backend>>,
+    ) -> std::io::Result<Self> {
+        let size = backend.borrow().size()?;
         Ok(Self {
             pos: (0, 0),
-            size: terminal::size()?,
-            content,
+            size,
+            content: content.into_content_source(),
             status_bar,
             commands,
             running: true,
             show_line_numbers: true,
             word_wrap: false,
             word_wrap_option: textwrap::Options::new(0),
+            search: None,
+            filter: None,
+            marks: Marks::default(),
+            search_theme: ContentStyle::new().attribute(Attribute::Reverse),
+            message: None,
+            tab_width: 8,
+            control_chars: ControlChars::default(),
+            show_whitespace: false,
+            special_chars_theme: ContentStyle::new().attribute(Attribute::Dim),
+            following: false,
+            history: HashMap::new(),
+            pending_keys: PendingKeys::default(),
+            key_timeout: Duration::from_secs(1),
+            sub_row: 0,
+            wrap_cache: RefCell::default(),
+            backend,
         })
     }
 
+    /// Pull new lines from [`State::content`].
+    ///
+    /// Returns true if any line was added.
+    pub fn poll_content(&mut self) -> bool {
+        if !self.content.poll() {
+            return false;
+        }
+        if let Some(filter) = &mut self.filter {
+            filter.update(&*self.content);
+        }
+        if let Some(search) = &mut self.search {
+            search.update(&ContentView::new(&*self.content, self.filter.as_ref()));
+        }
+        if self.following {
+            self.scroll_to_end();
+        }
+        true
+    }
+
     /// Returns true if the State is still runing.
     pub fn is_running(&self) -> bool {
         self.running
@@ -263,47 +609,24 @@ impl<'a> State<'a> {
                 .cmd
                 .iter()
                 .map(|cmd_type| match cmd_type {
-                    CommandType::Key(code) => match *code {
-                        KeyCode::Backspace => "Backspace".to_string(),
-                        KeyCode::Enter => "Enter".to_string(),
-                        KeyCode::Left => "Left".to_string(),
-                        KeyCode::Right => "Right".to_string(),
-                        KeyCode::Up => "Up".to_string(),
-                        KeyCode::Down => "Down".to_string(),
-                        KeyCode::Home => "Home".to_string(),
-                        KeyCode::End => "End".to_string(),
-                        KeyCode::PageUp => "PageUp".to_string(),
-                        KeyCode::PageDown => "PageDown".to_string(),
-                        KeyCode::Tab => "Tab".to_string(),
-                        KeyCode::BackTab => "BackTab".to_string(),
-                        KeyCode::Delete => "Delete".to_string(),
-                        KeyCode::Insert => "Insert".to_string(),
-                        KeyCode::F(n) => format!("F{}", n),
-                        KeyCode::Char(c) => c.to_string(),
-                        KeyCode::Null => "Null".to_string(),
-                        KeyCode::Esc => "Esc".to_string(),
-                        KeyCode::CapsLock => "CapsLock".to_string(),
-                        KeyCode::NumLock => "NumLock".to_string(),
-                        KeyCode::ScrollLock => "ScrollLock".to_string(),
-                        KeyCode::PrintScreen => "PrintScreen".to_string(),
-                        KeyCode::Pause => "Pause".to_string(),
-                        KeyCode::Menu => "Menu".to_string(),
-                        KeyCode::KeypadBegin => "KeypadBegin".to_string(),
-                        KeyCode::Media(_) => "MediaKey".to_string(),
-                        KeyCode::Modifier(_) => "ModifierKey".to_string(),
-                    },
+                    CommandType::Key(code) => sequence_name(&[KeyBinding::from(*code)]),
+                    CommandType::Keys(keys) => sequence_name(keys),
                     CommandType::Colon(s) => format!(":{}", s),
                 })
                 .collect::<Vec<String>>()
                 .join(", ");
             (name, command.desc.clone())
         });
-        let max_name_len = items.clone().map(|item| item.0.len()).max().unwrap();
+        let max_name_len = items
+            .clone()
+            .map(|item| text::width(&item.0))
+            .max()
+            .unwrap();
         let padding = max_name_len + 2;
 
         items
             .map(|(name, desc)| {
-                let name_len = name.len();
+                let name_len = text::width(&name);
                 format!(
                     "{}{gap}{}",
                     name,
@@ -320,6 +643,8 @@ impl<'a> State<'a> {
 
 impl<'a> State<'a> {
     /// Get line inducator of given line number.
+    ///
+    /// The separator shows the letter of a mark on the line, if there is one.
     fn get_line_inducator(
         &self,
         line_number: usize,
@@ -333,9 +658,14 @@ impl<'a> State<'a> {
                 line_number.to_string()
             };
 
+            let separator = match blank {
+                true => None,
+                false => self.mark_at(line_number - 1),
+            };
             format!(
-                "{:line_count$}│",
+                "{:line_count$}{}",
                 content,
+                separator.unwrap_or('│'),
                 line_count = max_line_number_width
             )
         } else {
@@ -343,74 +673,147 @@ impl<'a> State<'a> {
         }
     }
 
+    /// Width of the screen that is left for content, after the line numbers.
+    pub(crate) fn text_width(&self) -> usize {
+        let line_indicator_len = if self.show_line_numbers {
+            self.content.line_count().to_string().len() + 1
+        } else {
+            0
+        };
+        (self.size.0 as usize).saturating_sub(line_indicator_len)
+    }
+
+    /// Number of rows of the screen that are left for content, above the status bar.
+    pub(crate) fn text_height(&self) -> usize {
+        (self.size.1 as usize).saturating_sub(self.status_bar.line_layouts.len())
+    }
+
+    /// Options to expand tabs and control characters with.
+    pub(crate) fn expansion(&self) -> Expansion {
+        Expansion {
+            tab_width: self.tab_width,
+            control_chars: self.control_chars,
+            show_whitespace: self.show_whitespace,
+            style: self.special_chars_theme,
+        }
+    }
+
     /// Get text to be printed on terminal except for the [`StatusBar`].
+    ///
+    /// ANSI SGR sequences in the content are rendered as styles, tabs and control characters
+    /// are expanded, see [`State::tab_width`] and [`State::control_chars`].
     pub fn get_visible(&self) -> String {
-        let max_line_number_width = self.content.lines().count().to_string().len();
+        self.visible_lines()
+            .into_iter()
+            .map(|spans| Line::new(spans).to_ansi())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
 
-        let line_indicator_len = max_line_number_width + 1;
+    /// Styled spans of each row that [`State::get_visible`] returns.
+    pub(crate) fn visible_lines(&self) -> Vec<Vec<Span>> {
+        let max_line_number_width = self.content.line_count().to_string().len();
 
-        let lines: Box<dyn Iterator<Item = (usize, String)>> = match &self.word_wrap {
-            true => Box::new(self.content.lines().enumerate().flat_map(|(index, line)| {
-                let option = self
-                    .word_wrap_option
-                    .clone()
-                    .width(self.size.0 as usize - line_indicator_len);
-                textwrap::wrap(line, option)
+        let find_highlights = |text: &str| -> StyleRanges {
+            match &self.search {
+                Some(search) => search
+                    .line_matches(text)
                     .into_iter()
-                    .map(move |vline| (index, vline.to_string()))
-            })),
+                    .map(|range| (range, self.search_theme))
+                    .collect(),
+                None => vec![],
+            }
+        };
+        // Get a content line as text without escape sequences, its styles and search highlights.
+        let expansion = self.expansion();
+        let parse_line = |spans: Vec<Span>| {
+            let (text, styles) = join_spans(spans);
+            let highlights = find_highlights(&text);
+            let expanded = expansion.expand(&text);
+            let mut styles = expanded.map_styles(styles);
+            styles.extend(expanded.special.iter().cloned());
+            let highlights = expanded.map_styles(highlights);
+            (expanded.text, styles, highlights)
+        };
+
+        let view = self.view();
+        let lines: Box<dyn Iterator<Item = (usize, String, StyleRanges, StyleRanges)>> = match &self
+            .word_wrap
+        {
+            true => {
+                let (first_line, skip_rows) = (self.pos.1, self.top_row());
+                Box::new(
+                    view.spans_from(first_line)
+                        .enumerate()
+                        .flat_map(move |(index, spans)| {
+                            let index = first_line + index;
+                            let option = self.word_wrap_option.clone().width(self.text_width());
+                            let (text, styles, highlights) = parse_line(spans);
+                            let mut cursor = 0;
+                            textwrap::wrap(&text, &option)
+                                .into_iter()
+                                .enumerate()
+                                .map(|(row_index, row)| {
+                                    let indent = match row_index {
+                                        0 => option.initial_indent,
+                                        _ => option.subsequent_indent,
+                                    };
+                                    let (indent, range) = wrapped_part(&text, cursor, &row, indent);
+                                    cursor = range.end;
+                                    (
+                                        index,
+                                        row.into_owned(),
+                                        shift_styles(slice_styles(&styles, range.clone()), indent),
+                                        shift_styles(slice_styles(&highlights, range), indent),
+                                    )
+                                })
+                                .collect::<Vec<_>>()
+                        })
+                        .skip(skip_rows),
+                )
+            }
             false => Box::new(
-                self.content
-                    .lines()
+                view.spans_from(self.pos.1)
                     .enumerate()
-                    .map(|(index, line)| (index, line.to_owned())),
+                    .map(|(index, spans)| {
+                        let (text, styles, highlights) = parse_line(spans);
+                        (self.pos.1 + index, text, styles, highlights)
+                    }),
             ),
         };
 
         let mut last_index: usize = usize::MAX;
 
         lines
-            .skip(self.pos.1)
-            .take(self.size.1 as usize - self.status_bar.line_layouts.len())
-            .map(|(index, line)| -> String {
-                let line = format!(
-                    "{line_indicator}{visible_content_line}",
-                    line_indicator = self.get_line_inducator(
-                        index + 1,
-                        max_line_number_width,
-                        last_index == index
-                    ),
-                    visible_content_line = line
-                        .chars()
-                        .skip(self.pos.0)
-                        .take(self.size.0 as usize - line_indicator_len)
-                        .collect::<String>()
-                );
+            .take(self.text_height())
+            .map(|(index, line, styles, highlights)| {
+                let mut spans = vec![Span::raw(self.get_line_inducator(
+                    self.content_index(index) + 1,
+                    max_line_number_width,
+                    last_index == index,
+                ))];
+                spans.extend(render(
+                    &line,
+                    &[&styles, &highlights],
+                    self.pos.0,
+                    self.text_width(),
+                ));
                 last_index = index;
-                line
+                spans
             })
-            .collect::<Vec<String>>()
-            .join("\n")
+            .collect()
     }
 }
 
 impl<'a> State<'a> {
     /// Move cursor up.
     pub fn up(&mut self) -> bool {
-        if self.pos.1 != 0 {
-            self.pos.1 -= 1;
-            return true;
-        }
-        false
+        self.scroll_up(1)
     }
 
     /// Move cursor down.
     pub fn down(&mut self) -> bool {
-        if self.pos.1 != self.content.lines().count() - 1 {
-            self.pos.1 += 1;
-            return true;
-        }
-        false
+        self.scroll_down(1)
     }
 
     /// Move cursor left.
@@ -435,57 +838,90 @@ impl<'a> State<'a> {
 
     /// Move cursor one page up.
     pub fn pgup(&mut self) -> bool {
-        if self.pos.1 >= self.size.1 as usize {
-            self.pos.1 -= self.size.1 as usize - 1;
-            return true;
-        } else if self.pos.1 != 0 {
-            self.pos.1 = 0;
-            return true;
-        }
-        false
+        self.scroll_up((self.size.1 as usize).saturating_sub(1))
     }
 
     /// Move cursor one page down.
     pub fn pgdown(&mut self) -> bool {
-        let new = (self.pos.1 + self.size.1 as usize).min(self.content.lines().count()) - 1;
-        if new != self.pos.1 {
-            self.pos.1 = new;
-            return true;
-        }
-        false
+        self.scroll_down((self.size.1 as usize).saturating_sub(1))
+    }
+
+    /// Move cursor half a page up.
+    pub fn half_pgup(&mut self) -> bool {
+        self.scroll_up((self.size.1 as usize / 2).max(1))
+    }
+
+    /// Move cursor half a page down.
+    pub fn half_pgdown(&mut self) -> bool {
+        self.scroll_down((self.size.1 as usize / 2).max(1))
     }
 
     /// Move cursor to the start.
     pub fn home(&mut self) -> bool {
-        if self.pos.1 > 0 {
-            self.pos.1 = 0;
+        if self.pos.1 > 0 || self.top_row() > 0 {
+            self.record_jump();
+            self.scroll_to(0);
             return true;
         }
         false
     }
 
+    /// Keep the cursor at the end while new lines are added to [`State::content`].
+    ///
+    /// Any key press stops following.
+    pub fn follow(&mut self) -> bool {
+        self.following = true;
+        self.message = Some("Waiting for data... (any key to stop)".to_string());
+        self.record_jump();
+        self.scroll_to_end();
+        true
+    }
+
+    /// Stop following the end of the content.
+    ///
+    /// See: [`State::follow`]
+    pub fn stop_following(&mut self) -> bool {
+        std::mem::replace(&mut self.following, false)
+    }
+
+    /// Returns true if the cursor follows the end of the content.
+    pub fn is_following(&self) -> bool {
+        self.following
+    }
+
     /// Move cursor to the end.
     pub fn end(&mut self) -> bool {
-        let line_count = self.content.lines().count();
-        self.pos.1 = if line_count > self.size.1 as usize {
-            line_count - self.size.1 as usize + 1
-        } else {
-            0
-        };
+        self.record_jump();
+        self.scroll_to_end();
         true
     }
-}
 
-impl<'a> State<'a> {
-    /// Find and execute command matching with pressed key.
-    pub fn match_key_event(&mut self, code: KeyCode) -> bool {
-        let mut commands = self.commands.0.clone();
-        let found = commands
-            .iter_mut()
-            .find(|command| command.cmd.contains(&CommandType::Key(code)));
-        if let Some(Command { func, .. }) = found {
-            return func(self);
-        }
-        false
+    /// Go to the line with `number` in the line-number gutter, the first line is `1`.
+    ///
+    /// Numbers after the last line go to the last line.
+    pub fn goto_line(&mut self, number: usize) -> bool {
+        self.record_jump();
+        self.scroll_to(self.shown_index(number.saturating_sub(1)));
+        true
+    }
+
+    /// Go to `percent` of the shown lines, `0` is the first line and `100` the last one.
+    pub fn goto_percent(&mut self, percent: usize) -> bool {
+        let line_count = self.view().line_count();
+        self.record_jump();
+        self.scroll_to(line_count.saturating_sub(1) * percent.min(100) / 100);
+        true
+    }
+
+    /// Go to the line that contains the byte at `offset` of the content.
+    ///
+    /// Returns false if the offset is after the available lines.
+    pub fn goto_byte(&mut self, offset: usize) -> bool {
+        let Some(index) = self.content.line_at_byte(offset) else {
+            return false;
+        };
+        self.record_jump();
+        self.scroll_to(self.shown_index(index));
+        true
     }
 }
@@ -1,6 +1,6 @@
 use crossterm::style::{Attribute, Color, ContentStyle, StyledContent, Stylize};
 
-use crate::State;
+use crate::{text, ContentSource, State};
 
 /// Layout items for StatusBar.
 #[derive(Clone, Debug)]
@@ -17,6 +17,26 @@ pub enum StatusBarLayoutItem {
     ///
     /// See: [`StatusBar::title`]
     Title,
+    /// Display `[current/total] ` matches of the active search.
+    ///
+    /// The total is followed by `+` while the matches are still being counted.
+    ///
+    /// Nothing is displayed if there is no active search.
+    ///
+    /// See: [`State::search`]
+    SearchMatches,
+    /// Display `N of M lines ` while the content is filtered.
+    ///
+    /// `M` is followed by `+` while the lines are still being checked.
+    ///
+    /// See: [`State::set_filter`]
+    FilteredLines,
+    /// Display `Reading N% ` while the content is still being read.
+    ///
+    /// Nothing is displayed if the progress of the content is not known.
+    ///
+    /// See: [`ContentSource::progress`](crate::ContentSource::progress)
+    Progress,
 }
 
 /// Layout for a [`StatusBar`] line.
@@ -35,6 +55,9 @@ impl Default for StatusBarLayout {
         Self {
             left: vec![Title],
             right: vec![
+                Progress,
+                FilteredLines,
+                SearchMatches,
                 CurrentLine,
                 Text("/".to_string()),
                 LineCount,
@@ -48,12 +71,14 @@ impl Default for StatusBarLayout {
 impl StatusBarLayout {
     /// Get left and right parts as string.
     fn get_parts(&self, state: &State) -> [String; 2] {
-        let content_line_count = state.content.lines().count();
+        let content_line_count = state.view().line_count();
         [self.left.clone(), self.right.clone()].map(|part| {
             let mut output = String::new();
             for item in part {
                 output += &match item {
                     StatusBarLayoutItem::Text(s) => s.clone(),
+                    // Nothing is shown, e.g. when a filter matches no line.
+                    StatusBarLayoutItem::Persentage if content_line_count == 0 => "100".to_string(),
                     StatusBarLayoutItem::Persentage => {
                         format!(
                             "{:.0}",
@@ -61,8 +86,35 @@ impl StatusBarLayout {
                         )
                     }
                     StatusBarLayoutItem::LineCount => content_line_count.to_string(),
+                    StatusBarLayoutItem::CurrentLine if content_line_count == 0 => "0".to_string(),
                     StatusBarLayoutItem::CurrentLine => (state.pos.1 + 1).to_string(),
                     StatusBarLayoutItem::Title => state.status_bar.title.clone(),
+                    StatusBarLayoutItem::SearchMatches => match state.get_search() {
+                        Some(search) => format!(
+                            "[{}/{}{}] ",
+                            search
+                                .current()
+                                .map_or("-".to_string(), |current| (current + 1).to_string()),
+                            search.match_count(),
+                            if search.is_counted() { "" } else { "+" }
+                        ),
+                        None => String::new(),
+                    },
+                    StatusBarLayoutItem::FilteredLines => match state.get_filter() {
+                        Some(filter) => format!(
+                            "{} of {}{} lines ",
+                            filter.line_count(),
+                            state.content.line_count(),
+                            if filter.is_checked() { "" } else { "+" }
+                        ),
+                        None => String::new(),
+                    },
+                    StatusBarLayoutItem::Progress => match state.content.progress() {
+                        Some(progress) if !state.content.is_complete() => {
+                            format!("Reading {:.0}% ", progress * 100.0)
+                        }
+                        _ => String::new(),
+                    },
                 };
             }
             output
@@ -103,25 +155,31 @@ impl StatusBar {
 
     /// Get status bar text to be printed on terminal.
     pub fn get_visible(&self, state: &State) -> StyledContent<String> {
+        let last = self.line_layouts.len().saturating_sub(1);
         let bar = self
             .line_layouts
             .iter()
-            .map(|layout| {
-                let parts = layout.get_parts(state);
+            .enumerate()
+            .map(|(i, layout)| {
                 let width = state.size.0 as usize;
-                if parts[0].len() > width {
-                    parts[0].chars().take(width).collect()
-                } else if parts[0].len() + parts[1].len() > width {
+                let parts = match &state.message {
+                    Some(message) if i == last => [message.clone(), String::new()],
+                    _ => layout.get_parts(state),
+                };
+                let widths = parts.each_ref().map(|part| text::width(part));
+                if widths[0] > width {
+                    text::truncate(&parts[0], width)
+                } else if widths[0] + widths[1] > width {
                     format!(
                         "{left}{gap}",
                         left = parts[0],
-                        gap = " ".repeat(width - parts[0].len())
+                        gap = " ".repeat(width - widths[0])
                     )
                 } else {
                     format!(
                         "{left}{gap}{right}",
-                        left = parts[0].clone(),
-                        gap = " ".repeat(width - parts[0].len() - parts[1].len()),
+                        left = parts[0],
+                        gap = " ".repeat(width - widths[0] - widths[1]),
                         right = parts[1]
                     )
                 }
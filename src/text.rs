@@ -0,0 +1,406 @@
+use std::{borrow::Cow, ops::Range};
+
+use crossterm::style::ContentStyle;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{ControlChars, Span};
+
+/// Styles applied to byte ranges of a text.
+pub(crate) type StyleRanges = Vec<(Range<usize>, ContentStyle)>;
+
+/// Join `spans` into a text and the byte ranges of their styles.
+pub(crate) fn join_spans(spans: Vec<Span>) -> (String, StyleRanges) {
+    let mut text = String::new();
+    let mut styles = vec![];
+    for span in spans {
+        let start = text.len();
+        text += &span.text;
+        styles.push((start..text.len(), span.style));
+    }
+    (text, styles)
+}
+
+/// Get the part of `styles` that lies in `range`, relative to the start of `range`.
+pub(crate) fn slice_styles(styles: &StyleRanges, range: Range<usize>) -> StyleRanges {
+    styles
+        .iter()
+        .filter(|(r, _)| r.end > range.start && r.start < range.end)
+        .map(|(r, style)| {
+            (
+                r.start.max(range.start) - range.start..r.end.min(range.end) - range.start,
+                *style,
+            )
+        })
+        .collect()
+}
+
+/// Move `styles` right by `amount` bytes.
+pub(crate) fn shift_styles(styles: StyleRanges, amount: usize) -> StyleRanges {
+    styles
+        .into_iter()
+        .map(|(range, style)| (range.start + amount..range.end + amount, style))
+        .collect()
+}
+
+/// Find the part of `text` that a wrapped `row` shows, searching from `cursor`.
+///
+/// Rows are parts of the text after an indent, and before a hyphen that may be added.
+/// Returns the length of the indent and the range of the part in `text`.
+pub(crate) fn wrapped_part(
+    text: &str,
+    cursor: usize,
+    row: &str,
+    indent: &str,
+) -> (usize, Range<usize>) {
+    let indent = match row.starts_with(indent) {
+        true => indent.len(),
+        false => 0,
+    };
+    let mut part = &row[indent..];
+    loop {
+        // The empty part is always found.
+        if let Some(offset) = text[cursor..].find(part) {
+            let start = cursor + offset;
+            return (indent, start..start + part.len());
+        }
+        let end = part.char_indices().last().map_or(0, |(i, _)| i);
+        part = &part[..end];
+    }
+}
+
+/// Put `over` on top of `base`.
+///
+/// Colors of `over` replace the ones of `base`, attributes are combined.
+pub(crate) fn patch_style(base: ContentStyle, over: ContentStyle) -> ContentStyle {
+    ContentStyle {
+        foreground_color: over.foreground_color.or(base.foreground_color),
+        background_color: over.background_color.or(base.background_color),
+        underline_color: over.underline_color.or(base.underline_color),
+        attributes: base.attributes | over.attributes,
+    }
+}
+
+/// Number of columns that `text` takes on the screen.
+pub(crate) fn width(text: &str) -> usize {
+    text.width()
+}
+
+/// Graphemes of `text` that are in the `take` columns after the first `skip` columns,
+/// with their byte index.
+///
+/// Wide graphemes that are cut by the edges are replaced with spaces.
+pub(crate) fn columns(text: &str, skip: usize, take: usize) -> Vec<(usize, Cow<'_, str>)> {
+    let end = skip.saturating_add(take);
+    let mut column = 0;
+    let mut output = vec![];
+    for (i, grapheme) in text.grapheme_indices(true) {
+        let (start, width) = (column, grapheme.width());
+        column += width;
+        if start < skip && column <= skip {
+            continue;
+        }
+        if start >= end {
+            break;
+        }
+        match start < skip || column > end {
+            true => output.push((i, Cow::Owned(" ".repeat(column.min(end) - start.max(skip))))),
+            false => output.push((i, Cow::Borrowed(grapheme))),
+        }
+    }
+    output
+}
+
+/// Clip `text` to `width` columns.
+pub(crate) fn truncate(text: &str, width: usize) -> String {
+    columns(text, 0, width)
+        .into_iter()
+        .map(|(_, grapheme)| grapheme)
+        .collect()
+}
+
+/// Render the `take` columns of `text` after skipping `skip` of them, as styled spans.
+///
+/// Each layer of `layers` is patched on top of the previous ones.
+pub(crate) fn render(text: &str, layers: &[&StyleRanges], skip: usize, take: usize) -> Vec<Span> {
+    let style_at = |i: usize| {
+        layers
+            .iter()
+            .flat_map(|layer| layer.iter())
+            .filter(|(range, _)| range.contains(&i))
+            .fold(ContentStyle::new(), |style, (_, over)| {
+                patch_style(style, *over)
+            })
+    };
+
+    let mut spans = vec![];
+    let mut segment = Span::default();
+    for (i, grapheme) in columns(text, skip, take) {
+        let style = style_at(i);
+        if style != segment.style {
+            if !segment.text.is_empty() {
+                spans.push(std::mem::take(&mut segment));
+            }
+            segment.style = style;
+        }
+        segment.text += &grapheme;
+    }
+    if !segment.text.is_empty() {
+        spans.push(segment);
+    }
+    spans
+}
+
+pub(crate) fn render_segment(segment: &str, style: ContentStyle) -> String {
+    if segment.is_empty() || style == ContentStyle::new() {
+        segment.to_string()
+    } else {
+        style.apply(segment).to_string()
+    }
+}
+
+/// How tabs, control characters and whitespace are shown.
+///
+/// See: [`State::tab_width`](crate::State::tab_width)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Expansion {
+    pub tab_width: usize,
+    pub control_chars: ControlChars,
+    pub show_whitespace: bool,
+    pub style: ContentStyle,
+}
+
+/// Text with its tabs and control characters replaced by what is shown for them.
+#[derive(Clone, Debug)]
+pub(crate) struct Expanded {
+    pub text: String,
+    /// Styles of the replacements.
+    pub special: StyleRanges,
+    /// Offset in `text` of each byte offset of the original text, empty if they are the same.
+    offsets: Vec<usize>,
+}
+
+impl Expanded {
+    /// Offset in the expanded text of `index` in the original text.
+    pub fn offset(&self, index: usize) -> usize {
+        self.offsets.get(index).copied().unwrap_or(index)
+    }
+
+    /// Move `styles` of the original text to the expanded text.
+    pub fn map_styles(&self, styles: StyleRanges) -> StyleRanges {
+        if self.offsets.is_empty() {
+            return styles;
+        }
+        styles
+            .into_iter()
+            .map(|(range, style)| (self.offset(range.start)..self.offset(range.end), style))
+            .collect()
+    }
+}
+
+impl Expansion {
+    /// Replace tabs and control characters of `text`, and mark whitespace if it is shown.
+    pub fn expand(&self, text: &str) -> Expanded {
+        if !self.show_whitespace && !text.chars().any(char::is_control) {
+            return Expanded {
+                text: text.to_string(),
+                special: vec![],
+                offsets: vec![],
+            };
+        }
+        let trailing = text.trim_end_matches(' ').len();
+        let mut output = String::new();
+        let mut special = vec![];
+        let mut offsets = Vec::with_capacity(text.len() + 1);
+        let mut column = 0;
+        for (i, c) in text.char_indices() {
+            offsets.resize(i, output.len());
+            offsets.push(output.len());
+            let start = output.len();
+            match c {
+                '\t' => {
+                    let width = self.tab_width.max(1) - column % self.tab_width.max(1);
+                    match self.show_whitespace {
+                        true => output += &format!("→{}", " ".repeat(width - 1)),
+                        false => output += &" ".repeat(width),
+                    }
+                    column += width;
+                }
+                ' ' if self.show_whitespace && i >= trailing => {
+                    output.push('·');
+                    column += 1;
+                }
+                c if c.is_control() => {
+                    let replacement = self.control_chars.replacement(c);
+                    column += width(&replacement);
+                    output += &replacement;
+                }
+                c => {
+                    output.push(c);
+                    column += c.width().unwrap_or(0);
+                    continue;
+                }
+            }
+            if c != '\t' || self.show_whitespace {
+                special.push((start..output.len(), self.style));
+            }
+        }
+        offsets.resize(text.len(), output.len());
+        offsets.push(output.len());
+        if self.show_whitespace {
+            let start = output.len();
+            output.push('¶');
+            special.push((start..output.len(), self.style));
+        }
+        Expanded {
+            text: output,
+            special,
+            offsets,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::style::{Color, Stylize};
+
+    use super::*;
+
+    fn expansion(tab_width: usize, show_whitespace: bool) -> Expansion {
+        Expansion {
+            tab_width,
+            control_chars: ControlChars::Caret,
+            show_whitespace,
+            style: ContentStyle::new().blue(),
+        }
+    }
+
+    fn text_of(columns: Vec<(usize, Cow<'_, str>)>) -> Vec<(usize, String)> {
+        columns
+            .into_iter()
+            .map(|(i, grapheme)| (i, grapheme.into_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn columns_skip_and_take() {
+        assert_eq!(
+            text_of(columns("abcd", 1, 2)),
+            [(1, "b".to_string()), (2, "c".to_string())]
+        );
+        assert_eq!(columns("abcd", 4, 2), []);
+        assert_eq!(columns("abcd", 0, 0), []);
+        // Combining characters stay with their base.
+        assert_eq!(
+            text_of(columns("e\u{301}x", 0, 1)),
+            [(0, "e\u{301}".to_string())]
+        );
+    }
+
+    #[test]
+    fn columns_cut_wide_graphemes() {
+        // "日本" takes columns 0..4, the bytes start at 0 and 3.
+        assert_eq!(
+            text_of(columns("日本", 1, 3)),
+            [(0, " ".to_string()), (3, "本".to_string())]
+        );
+        assert_eq!(
+            text_of(columns("日本", 0, 3)),
+            [(0, "日".to_string()), (3, " ".to_string())]
+        );
+        assert_eq!(text_of(columns("日本", 1, 1)), [(0, " ".to_string())]);
+        assert_eq!(truncate("日本語", 5), "日本 ");
+    }
+
+    #[test]
+    fn tabs_expand_to_tab_stops() {
+        assert_eq!(expansion(4, false).expand("a\tb").text, "a   b");
+        assert_eq!(expansion(4, false).expand("abcd\tb").text, "abcd    b");
+        assert_eq!(
+            expansion(8, false).expand("\t\tc").text,
+            " ".repeat(16) + "c"
+        );
+        // Wide characters take two columns before the tab stop.
+        assert_eq!(expansion(4, false).expand("日\tb").text, "日  b");
+        assert_eq!(expansion(0, false).expand("a\tb").text, "a b");
+    }
+
+    #[test]
+    fn tabs_are_not_special_without_whitespace() {
+        let expanded = expansion(4, false).expand("a\tb");
+        assert_eq!(expanded.special, []);
+        // "b" moves from byte 2 to byte 4.
+        let styles = vec![(2..3, ContentStyle::new().red())];
+        assert_eq!(
+            expanded.map_styles(styles),
+            [(4..5, ContentStyle::new().red())]
+        );
+    }
+
+    #[test]
+    fn whitespace_is_shown_in_list_mode() {
+        let style = ContentStyle::new().blue();
+        let expanded = expansion(4, true).expand("a\tb c  ");
+        assert_eq!(expanded.text, "a→  b c··¶");
+        // The space between words is not trailing, so it is left as it is.
+        assert_eq!(
+            expanded.special,
+            [
+                (1..6, style),
+                (9..11, style),
+                (11..13, style),
+                (13..15, style)
+            ]
+        );
+        let styles = vec![(2..3, ContentStyle::new().red())];
+        assert_eq!(
+            expanded.map_styles(styles),
+            [(6..7, ContentStyle::new().red())]
+        );
+        assert_eq!(expansion(4, true).expand("").text, "¶");
+    }
+
+    #[test]
+    fn control_characters_in_caret_notation() {
+        let style = ContentStyle::new().blue();
+        let expanded = expansion(4, false).expand("a\x01b\x7f\x1b");
+        assert_eq!(expanded.text, "a^Ab^?^[");
+        assert_eq!(
+            expanded.special,
+            [(1..3, style), (4..6, style), (6..8, style)]
+        );
+        // The style of "b" and of the control character follow them.
+        let styles = vec![
+            (1..2, ContentStyle::new().red()),
+            (2..3, ContentStyle::new().with(Color::Green)),
+        ];
+        assert_eq!(
+            expanded.map_styles(styles),
+            [
+                (1..3, ContentStyle::new().red()),
+                (3..4, ContentStyle::new().with(Color::Green))
+            ]
+        );
+        // Tab stops count the columns of the notation.
+        assert_eq!(expansion(4, false).expand("\x01\tx").text, "^A  x");
+    }
+
+    #[test]
+    fn control_characters_as_pictures() {
+        let expansion = Expansion {
+            control_chars: ControlChars::Picture,
+            ..expansion(4, false)
+        };
+        assert_eq!(expansion.expand("a\x01\x7f").text, "a␁␡");
+        assert_eq!(expansion.expand("\u{85}").text, "\u{fffd}");
+    }
+
+    #[test]
+    fn plain_text_is_not_copied_into_offsets() {
+        let expanded = expansion(4, false).expand("abc");
+        assert_eq!(expanded.text, "abc");
+        assert_eq!(expanded.offset(2), 2);
+        let styles = vec![(0..3, ContentStyle::new().red())];
+        assert_eq!(expanded.map_styles(styles.clone()), styles);
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use crate::{text::Expansion, ContentSource, State};
+
+/// Number of rows that shown lines take when they are word-wrapped, for one text width,
+/// [`Expansion`] and indentation.
+///
+/// Lines are wrapped when their rows are asked for, and kept until the options change.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct WrapCache {
+    width: usize,
+    expansion: Option<Expansion>,
+    indents: (String, String),
+    break_words: bool,
+    /// Number of content lines when the rows were counted.
+    line_count: usize,
+    /// Rows of each shown line that is wrapped so far.
+    rows: HashMap<usize, usize>,
+}
+
+impl WrapCache {
+    /// Clear the rows if they are counted with other options, or for a replaced content.
+    fn update(&mut self, width: usize, expansion: Expansion, options: &textwrap::Options) {
+        let indents = (options.initial_indent, options.subsequent_indent);
+        if self.width != width
+            || self.expansion != Some(expansion)
+            || (self.indents.0.as_str(), self.indents.1.as_str()) != indents
+            || self.break_words != options.break_words
+        {
+            *self = Self {
+                width,
+                expansion: Some(expansion),
+                indents: (indents.0.to_string(), indents.1.to_string()),
+                break_words: options.break_words,
+                ..Default::default()
+            };
+        }
+    }
+}
+
+impl<'a> State<'a> {
+    /// Number of rows that the shown line at `line` takes on the screen.
+    ///
+    /// Always 1, unless word wrap is enabled.
+    pub(crate) fn line_rows(&self, line: usize) -> usize {
+        if !self.word_wrap {
+            return 1;
+        }
+        let (width, expansion) = (self.text_width(), self.expansion());
+        {
+            let mut cache = self.wrap_cache.borrow_mut();
+            cache.update(width, expansion, &self.word_wrap_option);
+            // Content lines are only appended, less lines means the content is replaced.
+            let line_count = self.content.line_count();
+            if cache.line_count > line_count {
+                cache.rows.clear();
+            }
+            cache.line_count = line_count;
+            if let Some(&rows) = cache.rows.get(&line) {
+                return rows;
+            }
+        }
+
+        let view = self.view();
+        let Some(text) = view.texts_from(line).next() else {
+            return 1;
+        };
+        let text = expansion.expand(&text).text;
+        let options = self.word_wrap_option.clone().width(width);
+        let rows = textwrap::wrap(&text, &options).len().max(1);
+        self.wrap_cache.borrow_mut().rows.insert(line, rows);
+        rows
+    }
+
+    /// Row of the top line that is shown at the top of the screen.
+    ///
+    /// Kept in the rows of the line, which change when the screen is resized.
+    pub(crate) fn top_row(&self) -> usize {
+        match self.word_wrap {
+            true => self.sub_row.min(self.line_rows(self.pos.1) - 1),
+            false => 0,
+        }
+    }
+
+    /// Move the top of the screen down by `rows` rows, until the last row is at the top.
+    ///
+    /// Returns true if it moved.
+    pub(crate) fn scroll_down(&mut self, rows: usize) -> bool {
+        let line_count = self.view().line_count();
+        let (mut line, mut row) = (self.pos.1, self.top_row());
+        let mut line_rows = self.line_rows(line);
+        for _ in 0..rows {
+            if row + 1 < line_rows {
+                row += 1;
+            } else if line + 1 < line_count {
+                line += 1;
+                row = 0;
+                line_rows = self.line_rows(line);
+            } else {
+                break;
+            }
+        }
+        self.set_top(line, row)
+    }
+
+    /// Move the top of the screen up by `rows` rows, until the first row is at the top.
+    ///
+    /// Returns true if it moved.
+    pub(crate) fn scroll_up(&mut self, rows: usize) -> bool {
+        let (mut line, mut row) = (self.pos.1, self.top_row());
+        for _ in 0..rows {
+            if row > 0 {
+                row -= 1;
+            } else if line > 0 {
+                line -= 1;
+                row = self.line_rows(line) - 1;
+            } else {
+                break;
+            }
+        }
+        self.set_top(line, row)
+    }
+
+    /// Show the last row at the bottom of the screen.
+    pub(crate) fn scroll_to_end(&mut self) {
+        let last = self.view().line_count().saturating_sub(1);
+        let rows = self.line_rows(last);
+        self.set_top(last, rows.saturating_sub(1));
+        self.scroll_up(self.text_height().saturating_sub(1));
+    }
+
+    /// Show the first row of the shown line at `line`, or of the last one, at the top.
+    pub(crate) fn scroll_to(&mut self, line: usize) {
+        let line = line.min(self.view().line_count().saturating_sub(1));
+        self.set_top(line, 0);
+    }
+
+    /// Returns true if the top changed.
+    fn set_top(&mut self, line: usize, row: usize) -> bool {
+        let changed = (line, row) != (self.pos.1, self.top_row());
+        self.pos.1 = line;
+        self.sub_row = row;
+        changed
+    }
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use std::io;
+
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+
+use common::{numbered_lines, pager, press, top};
+use pager_rs::{run, Backend, Buffer, CommandList, TestBackend};
+
+#[test]
+fn frames_are_recorded_at_each_flush() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Down, KeyModifiers::NONE);
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    let backend = backend.borrow();
+    assert_eq!(backend.frames()[0][..4], ["0", "1", "2", "3"]);
+    assert_eq!(backend.screen()[..4], ["1", "2", "3", "4"]);
+    assert!(backend.screen()[4].starts_with("T"));
+}
+
+#[test]
+fn running_out_of_events() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Down, KeyModifiers::NONE);
+    let err = run(&mut state).unwrap_err();
+
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(top(&backend), "1");
+}
+
+#[test]
+fn prompt_is_drawn_on_the_last_row() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, "/ab\x1bq");
+    run(&mut state).unwrap();
+
+    let backend = backend.borrow();
+    let frames = backend.frames();
+    assert_eq!(frames[frames.len() - 2][4], "/ab");
+    assert_eq!(backend.cursor(), None);
+}
+
+#[test]
+fn resize_changes_the_screen() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    backend.borrow_mut().push_event(Event::Resize(30, 3));
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    let screen = backend.borrow().screen();
+    assert_eq!(screen.len(), 3);
+    assert_eq!(screen[..2], ["0", "1"]);
+}
+
+#[test]
+fn running_out_of_events_in_help() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 5);
+    press(&backend, "h");
+    let err = run(&mut state).unwrap_err();
+
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn help_runs_on_the_same_backend() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 5);
+    press(&backend, "hqq");
+    run(&mut state).unwrap();
+
+    let backend = backend.borrow();
+    let frames = backend.frames();
+    assert!(frames.iter().any(|frame| frame[4].contains("Quit (q)")));
+    assert_eq!(backend.screen()[0], "0");
+}
+
+#[test]
+fn scrolling_a_line_draws_one_row() {
+    let content: String = (0..100).map(|i| format!("line {}\n", i)).collect();
+    let (mut state, backend) = pager(content, CommandList::default(), 20, 10);
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Down, KeyModifiers::NONE);
+    let err = run(&mut state).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    // Only the row that scrolled in is drawn, the other ones are moved by scrolling.
+    let backend = backend.borrow();
+    assert_eq!(backend.screen()[0], "line 1");
+    assert_eq!(backend.screen()[8], "line 9");
+    assert_eq!(backend.screen()[9], "T         2/100 (2%)");
+    // The new row except its blank cell, and the two digits of the status bar that changed.
+    assert_eq!(backend.drawn_cells(), "line9".len() + 2);
+}
+
+#[test]
+fn changing_a_cell_draws_one_cell() {
+    let mut backend = TestBackend::new(10, 3);
+    let mut buffer = Buffer::new(10, 3);
+    buffer.print_at(0, "one");
+    buffer.print_at(1, "two");
+    backend.draw(&buffer, 0..2).unwrap();
+    assert_eq!(backend.drawn_cells(), 6);
+
+    buffer.print_at(1, "twa");
+    backend.draw(&buffer, 0..2).unwrap();
+    assert_eq!(backend.drawn_cells(), 1);
+
+    backend.draw(&buffer, 0..2).unwrap();
+    assert_eq!(backend.drawn_cells(), 0);
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc};
+
+use common::{numbered_lines, pager, press, status, top};
+use pager_rs::{run, Command, CommandList, CommandType};
+
+/// Default commands, with `:echo` and `:edit` that record their arguments in `args`.
+fn commands(args: &Rc<RefCell<Vec<String>>>) -> CommandList {
+    let command = |name: &str| {
+        let args = args.clone();
+        Command::with_args(
+            vec![CommandType::Colon(name.to_string())],
+            format!("Record the arguments of {}", name),
+            move |_, given| {
+                args.borrow_mut().extend(given.args.iter().cloned());
+                Ok(false)
+            },
+        )
+    };
+    CommandList::combine(vec![
+        CommandList::default(),
+        CommandList(vec![command("echo"), command("edit")]),
+    ])
+}
+
+#[test]
+fn arguments_are_parsed() {
+    let args = Rc::new(RefCell::new(vec![]));
+    let (mut state, backend) = pager(numbered_lines(10), commands(&args), 40, 5);
+    press(&backend, ":echo 'C:\\tmp\\x' \"a b\" c\\ d\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(*args.borrow(), ["C:\\tmp\\x", "a b", "c d"]);
+}
+
+#[test]
+fn unique_prefix_runs_the_command() {
+    let args = Rc::new(RefCell::new(vec![]));
+    let (mut state, backend) = pager(numbered_lines(10), commands(&args), 40, 5);
+    press(&backend, ":ec x\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(*args.borrow(), ["x"]);
+}
+
+#[test]
+fn ambiguous_prefix() {
+    let args = Rc::new(RefCell::new(vec![]));
+    let (mut state, backend) = pager(numbered_lines(10), commands(&args), 40, 5);
+    press(&backend, ":e x\n");
+    let _ = run(&mut state);
+
+    assert_eq!(status(&backend), "Ambiguous command: e (echo, edit)");
+    assert!(args.borrow().is_empty());
+}
+
+#[test]
+fn unterminated_quote() {
+    let (mut state, backend) = pager(numbered_lines(10), CommandList::default(), 40, 5);
+    press(&backend, ":set 'wrap\n");
+    let _ = run(&mut state);
+
+    assert_eq!(status(&backend), "unterminated '");
+}
+
+#[test]
+fn set_several_options() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, ":set nonumber wrap\nq");
+    state.show_line_numbers = true;
+    run(&mut state).unwrap();
+
+    assert!(!state.show_line_numbers);
+    assert!(state.word_wrap);
+}
+
+#[test]
+fn unknown_command() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 5);
+    press(&backend, ":nothing\n");
+    let _ = run(&mut state);
+
+    assert_eq!(status(&backend), "Unknown command: nothing");
+}
+
+#[test]
+fn command_errors_start_with_its_name() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 5);
+    press(&backend, ":go x\n");
+    let _ = run(&mut state);
+
+    assert_eq!(status(&backend), "goto: invalid argument 'x'");
+}
+
+#[test]
+fn line_number_alone_goes_to_the_line() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, ":20\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "19");
+}
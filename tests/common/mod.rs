@@ -0,0 +1,53 @@
+#![allow(dead_code)]
+
+use std::{cell::RefCell, rc::Rc};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use pager_rs::{CommandList, IntoContentSource, State, StatusBar, TestBackend};
+
+/// Lines `0` to `count - 1`.
+pub fn numbered_lines(count: usize) -> String {
+    (0..count).map(|i| format!("{}\n", i)).collect()
+}
+
+/// A [`State`] of `content` on a [`TestBackend`] of `width` and `height`, without line numbers.
+pub fn pager<C: IntoContentSource>(
+    content: C,
+    commands: CommandList,
+    width: u16,
+    height: u16,
+) -> (State<'static>, Rc<RefCell<TestBackend>>) {
+    let backend = Rc::new(RefCell::new(TestBackend::new(width, height)));
+    let mut state = State::with_backend(
+        content,
+        StatusBar::new("T".to_string()),
+        commands,
+        backend.clone(),
+    )
+    .unwrap();
+    state.show_line_numbers = false;
+    (state, backend)
+}
+
+/// Press a key for each character of `keys`, `\n` is `Enter` and `\x1b` is `Esc`.
+pub fn press(backend: &Rc<RefCell<TestBackend>>, keys: &str) {
+    let mut backend = backend.borrow_mut();
+    for c in keys.chars() {
+        let code = match c {
+            '\n' => KeyCode::Enter,
+            '\x1b' => KeyCode::Esc,
+            c => KeyCode::Char(c),
+        };
+        backend.push_key(code, KeyModifiers::NONE);
+    }
+}
+
+/// First row of the screen.
+pub fn top(backend: &Rc<RefCell<TestBackend>>) -> String {
+    backend.borrow().screen()[0].clone()
+}
+
+/// Last row of the screen.
+pub fn status(backend: &Rc<RefCell<TestBackend>>) -> String {
+    backend.borrow().screen().last().unwrap().clone()
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use common::{numbered_lines, pager, press};
+use pager_rs::{run, CommandList};
+
+#[test]
+fn command_names() {
+    let (state, _) = pager(numbered_lines(10), CommandList::default(), 40, 5);
+    assert_eq!(state.complete_colon("g"), (0, vec!["goto".to_string()]));
+    assert_eq!(state.complete_colon("b"), (0, vec!["byte".to_string()]));
+    assert!(state.complete_colon("").1.len() > 4);
+    assert_eq!(state.complete_colon("x"), (0, vec![]));
+}
+
+#[test]
+fn command_arguments() {
+    let (state, _) = pager(numbered_lines(10), CommandList::default(), 40, 5);
+    assert_eq!(
+        state.complete_colon("set no"),
+        (4, vec!["nowrap".into(), "nonumber".into(), "nolist".into()])
+    );
+    // Abbreviated command names and later arguments are completed too.
+    assert_eq!(
+        state.complete_colon("se wrap tab"),
+        (8, vec!["tabstop=".to_string()])
+    );
+    assert_eq!(state.complete_colon("goto 1"), (5, vec![]));
+    assert_eq!(state.complete_colon("nothing a"), (8, vec![]));
+}
+
+#[test]
+fn tab_in_the_prompt() {
+    let (mut state, backend) = pager(numbered_lines(10), CommandList::default(), 40, 5);
+    press(&backend, ":se");
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Tab, KeyModifiers::NONE);
+    press(&backend, " now");
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Tab, KeyModifiers::NONE);
+    press(&backend, "\nq");
+    run(&mut state).unwrap();
+
+    assert!(!state.word_wrap);
+    let frames = backend.borrow().frames().to_vec();
+    assert!(frames.iter().any(|frame| frame[4] == ":set nowrap"));
+}
+
+#[test]
+fn tab_and_back_tab_cycle() {
+    let (mut state, backend) = pager(numbered_lines(10), CommandList::default(), 40, 5);
+    state.show_line_numbers = true;
+    press(&backend, ":set no");
+    {
+        let mut backend = backend.borrow_mut();
+        backend.push_key(KeyCode::Tab, KeyModifiers::NONE);
+        backend.push_key(KeyCode::Tab, KeyModifiers::NONE);
+        backend.push_key(KeyCode::Tab, KeyModifiers::NONE);
+        backend.push_key(KeyCode::BackTab, KeyModifiers::SHIFT);
+    }
+    press(&backend, "\nq");
+    run(&mut state).unwrap();
+
+    // nowrap, nonumber, nolist and back to nonumber.
+    let frames = backend.borrow().frames().to_vec();
+    let prompts: Vec<&str> = frames.iter().map(|frame| frame[4].as_str()).collect();
+    assert_eq!(
+        prompts[prompts.len() - 6..prompts.len() - 1],
+        [
+            ":set no",
+            ":set nowrap",
+            ":set nonumber",
+            ":set nolist",
+            ":set nonumber"
+        ]
+    );
+    assert!(!state.show_line_numbers);
+}
+
+#[test]
+fn back_tab_starts_from_the_last_candidate() {
+    let (mut state, backend) = pager(numbered_lines(10), CommandList::default(), 40, 5);
+    state.show_whitespace = true;
+    press(&backend, ":set no");
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::BackTab, KeyModifiers::SHIFT);
+    press(&backend, "\nq");
+    run(&mut state).unwrap();
+
+    assert!(!state.show_whitespace);
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use std::io;
+
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+
+use common::{numbered_lines, pager, press, status, top};
+use pager_rs::{run, CommandList, Line, Span};
+
+#[test]
+fn filter_shows_matching_lines() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 4);
+    press(&backend, "&5\nq");
+    run(&mut state).unwrap();
+
+    let screen = backend.borrow().screen();
+    assert_eq!(screen[..3], ["5", "15", "25"]);
+    assert!(status(&backend).ends_with(" 19 of 100 lines 1/19 (5%)"));
+}
+
+#[test]
+fn inverted_filter() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 4);
+    press(&backend, "&![0-8]\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(backend.borrow().screen()[..2], ["9", "99"]);
+}
+
+#[test]
+fn empty_filter_result() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 4);
+    press(&backend, "&nothing\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "");
+    assert!(status(&backend).ends_with(" 0 of 100 lines 0/0 (100%)"));
+}
+
+#[test]
+fn clearing_the_filter_keeps_the_line() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 4);
+    press(&backend, "&7\n");
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Down, KeyModifiers::NONE);
+    press(&backend, "&\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "17");
+    assert!(state.get_filter().is_none());
+}
+
+#[test]
+fn search_in_filtered_lines() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 4);
+    press(&backend, "&3\n/3$\nn");
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "13");
+    assert!(status(&backend).contains("[2/10]"));
+}
+
+#[test]
+fn filter_escape_sequences_of_spans() {
+    let mut lines: Vec<Line> = (0..30).map(|i| Line::from(format!("{}", i))).collect();
+    lines[20] = Line::from(Span::raw("\x1b[31mred"));
+    let (mut state, backend) = pager(lines, CommandList::default(), 20, 4);
+    press(&backend, "&31m\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(backend.borrow().screen()[..2], ["^[[31mred", ""]);
+}
+
+#[test]
+fn lines_are_filtered_between_events() {
+    let (mut state, backend) = pager(numbered_lines(25_000), CommandList::default(), 40, 4);
+    press(&backend, "&5$\n");
+    let err = run(&mut state).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    // A chunk is checked when the filter is set and one after the event.
+    assert!(status(&backend).ends_with(" 2000 of 25000+ lines 1/2000 (0%)"));
+    assert!(!state.get_filter().unwrap().is_checked());
+
+    backend.borrow_mut().push_event(Event::FocusGained);
+    let _ = run(&mut state);
+    assert!(status(&backend).ends_with(" 2500 of 25000 lines 1/2500 (0%)"));
+    assert!(state.get_filter().unwrap().is_checked());
+}
+
+#[test]
+fn view_moves_to_the_top_line_once_it_is_filtered() {
+    let (mut state, backend) = pager(numbered_lines(25_000), CommandList::default(), 40, 4);
+    press(&backend, ":22000\n&0$\n");
+    let _ = run(&mut state);
+    // The top line is not checked yet, the last matching line is shown.
+    assert_eq!(top(&backend), "9990");
+
+    backend.borrow_mut().push_event(Event::FocusGained);
+    let _ = run(&mut state);
+    assert_eq!(top(&backend), "22000");
+}
+
+#[test]
+fn moving_while_filtering_keeps_the_view() {
+    let (mut state, backend) = pager(numbered_lines(25_000), CommandList::default(), 40, 4);
+    press(&backend, ":22000\n&0$\n");
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Down, KeyModifiers::NONE);
+    backend.borrow_mut().push_event(Event::FocusGained);
+    let _ = run(&mut state);
+
+    assert_eq!(top(&backend), "10000");
+    assert!(state.get_filter().unwrap().is_checked());
+}
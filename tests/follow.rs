@@ -0,0 +1,65 @@
+mod common;
+
+use std::{cell::RefCell, rc::Rc, sync::mpsc::channel};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+use common::{numbered_lines, pager, press, status, top};
+use pager_rs::{run, ChannelSource, CommandList, TestBackend};
+
+/// Release `code`, after it is pressed.
+fn release(backend: &Rc<RefCell<TestBackend>>, code: KeyCode) {
+    let mut release = KeyEvent::new(code, KeyModifiers::NONE);
+    release.kind = KeyEventKind::Release;
+    backend.borrow_mut().push_event(Event::Key(release));
+}
+
+#[test]
+fn follow_shows_the_end() {
+    let (sender, receiver) = channel();
+    for i in 0..10 {
+        sender.send(i.to_string()).unwrap();
+    }
+    let (mut state, backend) = pager(ChannelSource::new(receiver), CommandList::default(), 20, 5);
+    press(&backend, "F");
+    let _ = run(&mut state);
+
+    assert!(state.is_following());
+    assert_eq!(top(&backend), "6");
+}
+
+#[test]
+fn key_release_does_not_stop_following() {
+    let (sender, receiver) = channel();
+    sender.send("0".to_string()).unwrap();
+    let (mut state, backend) = pager(ChannelSource::new(receiver), CommandList::default(), 20, 5);
+    press(&backend, "F");
+    release(&backend, KeyCode::Char('F'));
+    let _ = run(&mut state);
+
+    assert!(state.is_following());
+}
+
+#[test]
+fn key_press_stops_following() {
+    let (sender, receiver) = channel();
+    sender.send("0".to_string()).unwrap();
+    let (mut state, backend) = pager(ChannelSource::new(receiver), CommandList::default(), 20, 5);
+    press(&backend, "F");
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Up, KeyModifiers::NONE);
+    let _ = run(&mut state);
+
+    assert!(!state.is_following());
+}
+
+#[test]
+fn key_release_keeps_the_message() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 5);
+    press(&backend, "/nothing\n");
+    release(&backend, KeyCode::Enter);
+    let _ = run(&mut state);
+
+    assert_eq!(status(&backend), "Pattern not found");
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{numbered_lines, pager, press, status, top};
+use pager_rs::{run, CommandList};
+
+#[test]
+fn goto_line_command() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, ":50\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "49");
+}
+
+#[test]
+fn goto_command_with_name() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, ":goto 7\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "6");
+}
+
+#[test]
+fn count_before_g() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, "30Gq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "29");
+}
+
+#[test]
+fn percent() {
+    let (mut state, backend) = pager(numbered_lines(101), CommandList::default(), 20, 5);
+    press(&backend, "50%q");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "50");
+}
+
+#[test]
+fn percent_needs_a_count() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 40, 5);
+    press(&backend, "%");
+    let _ = run(&mut state);
+
+    assert!(status(&backend).contains("needs a count"));
+}
+
+#[test]
+fn byte_offset() {
+    // Lines `0` to `9` take 2 bytes each, the following ones 3 bytes.
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, ":byte 26\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "12");
+}
+
+#[test]
+fn byte_offset_after_the_end() {
+    let (mut state, backend) = pager(numbered_lines(10), CommandList::default(), 40, 5);
+    press(&backend, ":byte 20\n");
+    let _ = run(&mut state);
+
+    assert!(status(&backend).contains("offset is after the end"));
+}
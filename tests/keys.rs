@@ -0,0 +1,83 @@
+mod common;
+
+use std::{cell::Cell, rc::Rc};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use common::{numbered_lines, pager, press, top};
+use pager_rs::{run, Command, CommandList, CommandType, KeyBinding};
+
+/// Default commands and one bound to `key` that counts how often it runs.
+fn counted(key: KeyBinding) -> (CommandList, Rc<Cell<usize>>) {
+    let count = Rc::new(Cell::new(0));
+    let counter = count.clone();
+    let command = Command::new(
+        vec![CommandType::Keys(vec![key])],
+        "Count".to_string(),
+        move |_| {
+            counter.set(counter.get() + 1);
+            false
+        },
+    );
+    let commands = CommandList::combine(vec![CommandList::default(), CommandList(vec![command])]);
+    (commands, count)
+}
+
+#[test]
+fn count_repeats_a_command() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::less(), 20, 5);
+    press(&backend, "10jq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "10");
+}
+
+#[test]
+fn key_sequence() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::less(), 20, 5);
+    press(&backend, "jjZZ");
+    run(&mut state).unwrap();
+
+    assert!(!state.is_running());
+    assert_eq!(top(&backend), "2");
+}
+
+#[test]
+fn vi_sequence_with_shorter_prefix() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::vi(), 20, 5);
+    press(&backend, "Ggg");
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "0");
+}
+
+#[test]
+fn back_tab_is_bound_without_shift() {
+    let (commands, count) = counted(KeyBinding::from(KeyCode::BackTab));
+    let (mut state, backend) = pager(numbered_lines(10), commands, 20, 5);
+    {
+        let mut backend = backend.borrow_mut();
+        backend.push_key(KeyCode::BackTab, KeyModifiers::SHIFT);
+        backend.push_key(KeyCode::BackTab, KeyModifiers::NONE);
+    }
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(count.get(), 2);
+}
+
+#[test]
+fn shift_is_kept_for_arrows() {
+    let (commands, count) = counted(KeyBinding::new(KeyCode::Up, KeyModifiers::SHIFT));
+    let (mut state, backend) = pager(numbered_lines(10), commands, 20, 5);
+    {
+        let mut backend = backend.borrow_mut();
+        backend.push_key(KeyCode::Up, KeyModifiers::SHIFT);
+        backend.push_key(KeyCode::Up, KeyModifiers::NONE);
+    }
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(count.get(), 1);
+}
@@ -0,0 +1,69 @@
+mod common;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use common::{numbered_lines, pager, press, top};
+use pager_rs::{run, CommandList, Mark};
+
+#[test]
+fn jump_to_mark() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, "/^19$\nmaG'aq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "19");
+    assert_eq!(
+        state.get_mark('a'),
+        Some(Mark {
+            line: 19,
+            column: 0
+        })
+    );
+}
+
+#[test]
+fn missing_mark() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, "'b");
+    let _ = run(&mut state);
+
+    assert_eq!(backend.borrow().screen()[4], "Mark not set");
+}
+
+#[test]
+fn previous_position() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, "/^29$\nG''");
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "29");
+}
+
+#[test]
+fn mark_is_shown_in_the_gutter() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    state.show_line_numbers = true;
+    press(&backend, "mzq");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "1  z0");
+}
+
+#[test]
+fn jump_list() {
+    let (mut state, backend) = pager(numbered_lines(100), CommandList::default(), 20, 5);
+    press(&backend, "/^9$\n/^49$\nG");
+    {
+        let mut backend = backend.borrow_mut();
+        backend.push_key(KeyCode::Char('o'), KeyModifiers::CONTROL);
+        backend.push_key(KeyCode::Char('o'), KeyModifiers::CONTROL);
+        backend.push_key(KeyCode::Tab, KeyModifiers::NONE);
+    }
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    let frames = backend.borrow().frames().to_vec();
+    let tops: Vec<&str> = frames.iter().map(|frame| frame[0].as_str()).collect();
+    assert_eq!(tops[tops.len() - 3..], ["49", "9", "49"]);
+}
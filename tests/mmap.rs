@@ -0,0 +1,141 @@
+use std::{
+    fs,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use pager_rs::{ContentSource, MmapSource, TextSource};
+
+/// A file of `text` in the temporary directory, removed on drop.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(text: &str) -> Self {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "pager-rs-mmap-{}-{}",
+            process::id(),
+            COUNT.fetch_add(1, Ordering::SeqCst)
+        );
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, text).unwrap();
+        Self(path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// An [`MmapSource`] of `file` that is indexed completely.
+fn mmap(file: &TempFile) -> MmapSource {
+    let mut source = MmapSource::open(&file.0).unwrap();
+    while !source.is_complete() {
+        source.poll();
+    }
+    source
+}
+
+/// Check that [`MmapSource`] has the same lines as [`TextSource`] for `text`.
+fn assert_same_lines(text: &str) {
+    let file = TempFile::new(text);
+    let source = mmap(&file);
+    let expected = TextSource::new(text.to_string());
+
+    let count = expected.line_count();
+    assert_eq!(source.line_count(), count);
+    for index in 0..=count {
+        assert_eq!(source.line(index), expected.line(index), "line {}", index);
+    }
+    for start in (0..=count + 1).chain([63, 64, 65, 128]) {
+        assert!(
+            source.lines_from(start).eq(expected.lines_from(start)),
+            "lines from {}",
+            start
+        );
+    }
+    for offset in 0..=text.len() + 1 {
+        assert_eq!(
+            source.line_at_byte(offset),
+            expected.line_at_byte(offset),
+            "line at byte {}",
+            offset
+        );
+    }
+}
+
+fn numbered_lines(count: usize, ending: &str) -> String {
+    (0..count)
+        .map(|i| format!("line {}{}", i, ending))
+        .collect()
+}
+
+#[test]
+fn lines_match_text_source() {
+    assert_same_lines(&numbered_lines(1001, "\n"));
+}
+
+#[test]
+fn crlf_line_endings() {
+    assert_same_lines(&numbered_lines(1001, "\r\n"));
+    assert_same_lines("a\r\n\r\nb\r");
+}
+
+#[test]
+fn no_trailing_newline() {
+    let text = numbered_lines(1001, "\n");
+    assert_same_lines(text.trim_end());
+    assert_same_lines("a");
+    assert_same_lines("\n\na");
+}
+
+#[test]
+fn empty_file() {
+    assert_same_lines("");
+    assert_same_lines("\n");
+}
+
+#[test]
+fn lines_around_checkpoints() {
+    let text = numbered_lines(200, "\n");
+    let source = mmap(&TempFile::new(&text));
+    for index in [0, 63, 64, 65, 127, 128, 129, 199] {
+        let line = format!("line {}", index);
+        assert_eq!(source.line(index).as_deref(), Some(line.as_str()));
+        assert_eq!(
+            source.lines_from(index).next().as_deref(),
+            Some(line.as_str())
+        );
+        let offset = text.find(&format!("{}\n", line)).unwrap();
+        assert_eq!(source.line_at_byte(offset), Some(index));
+        assert_eq!(source.line_at_byte(offset + line.len()), Some(index));
+    }
+    assert_eq!(source.line(200), None);
+}
+
+#[test]
+fn lines_across_index_chunks() {
+    // More than the 4 MiB that the indexing thread scans at once.
+    let text = numbered_lines(500_000, "\n");
+    let file = TempFile::new(&text);
+    let source = mmap(&file);
+    let expected = TextSource::new(text.clone());
+
+    assert_eq!(source.line_count(), 500_000);
+    let boundary = 4 * 1024 * 1024;
+    let index = expected.line_at_byte(boundary).unwrap();
+    for index in index - 65..index + 65 {
+        assert_eq!(source.line(index), expected.line(index));
+    }
+    for offset in boundary - 100..boundary + 100 {
+        assert_eq!(source.line_at_byte(offset), expected.line_at_byte(offset));
+    }
+    assert!(source
+        .lines_from(index - 1)
+        .take(200)
+        .eq(expected.lines_from(index - 1).take(200)));
+    assert_eq!(source.line(499_999).as_deref(), Some("line 499999"));
+}
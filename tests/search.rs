@@ -0,0 +1,82 @@
+mod common;
+
+use common::{pager, press, status, top};
+use pager_rs::{run, CommandList};
+
+/// 100 lines, every fifth one has two matches.
+fn content() -> String {
+    (0..100)
+        .map(|i| match i % 5 {
+            0 => format!("{} match and match\n", i),
+            _ => format!("{}\n", i),
+        })
+        .collect()
+}
+
+#[test]
+fn search_jumps_to_matches() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 40, 6);
+    press(&backend, "/match\nnn");
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    // The second `n` goes from the second match of line 0 to line 5.
+    assert_eq!(top(&backend), "5 match and match");
+    assert!(status(&backend).contains("[3/40]"));
+}
+
+#[test]
+fn previous_match_goes_back() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 40, 6);
+    press(&backend, "/match\nnnnN");
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "5 match and match");
+    assert!(status(&backend).contains("[3/40]"));
+}
+
+#[test]
+fn search_starts_from_the_view_after_moving() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 40, 6);
+    press(&backend, "/match\nGNNN");
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    // The view is at line 95 after `G`, so `N` goes to the matches before it.
+    assert_eq!(top(&backend), "85 match and match");
+    assert!(status(&backend).contains("[36/40]"));
+}
+
+#[test]
+fn backward_search() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 40, 6);
+    press(&backend, "G?match\n");
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(top(&backend), "90 match and match");
+}
+
+#[test]
+fn missing_pattern_shows_a_message() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 40, 6);
+    press(&backend, "/nothing\n");
+    let _ = run(&mut state);
+
+    assert_eq!(top(&backend), "0 match and match");
+    assert_eq!(status(&backend), "Pattern not found");
+}
+
+#[test]
+fn preview_is_undone_by_escape() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 40, 6);
+    press(&backend, "/10 m");
+    press(&backend, "\x1bq");
+    run(&mut state).unwrap();
+
+    let frames = backend.borrow().frames().to_vec();
+    assert!(frames.iter().any(|frame| frame[0] == "10 match and match"));
+    assert_eq!(top(&backend), "0 match and match");
+    assert!(state.get_search().is_none());
+}
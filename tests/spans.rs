@@ -0,0 +1,95 @@
+mod common;
+
+use crossterm::style::{Attribute, Color, ContentStyle, Stylize};
+
+use common::{pager, press};
+use pager_rs::{run, CommandList, Line, Span};
+
+#[test]
+fn span_styles_are_shown() {
+    let style = ContentStyle::new().bold().with(Color::Blue);
+    let lines = vec![Line::from(vec![Span::new("bold", style), " plain".into()])];
+    let (mut state, backend) = pager(lines, CommandList::default(), 20, 3);
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    let backend = backend.borrow();
+    assert_eq!(backend.screen()[0], "bold plain");
+    assert!(backend
+        .cell(0, 0)
+        .unwrap()
+        .style
+        .attributes
+        .has(Attribute::Bold));
+    assert_eq!(
+        backend.cell(0, 0).unwrap().style.foreground_color,
+        Some(Color::Blue)
+    );
+    assert_eq!(backend.cell(5, 0).unwrap().style, ContentStyle::new());
+}
+
+#[test]
+fn span_styles_are_kept() {
+    let mut style = ContentStyle::new();
+    style.attributes.set(Attribute::Undercurled);
+    style.attributes.set(Attribute::Fraktur);
+    let lines = vec![Line::from(vec![Span::new("curly", style), " plain".into()])];
+    let (mut state, backend) = pager(lines, CommandList::default(), 20, 3);
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    let backend = backend.borrow();
+    assert_eq!(backend.screen()[0], "curly plain");
+    assert_eq!(backend.cell(0, 0).unwrap().style, style);
+    assert_eq!(backend.cell(6, 0).unwrap().style, ContentStyle::new());
+}
+
+#[test]
+fn escape_sequences_in_spans_are_not_parsed() {
+    let lines = vec![Line::from(Span::raw("\x1b[31mred"))];
+    let (mut state, backend) = pager(lines, CommandList::default(), 20, 3);
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    let backend = backend.borrow();
+    assert_eq!(backend.screen()[0], "^[[31mred");
+    assert_eq!(backend.cell(6, 0).unwrap().style.foreground_color, None);
+}
+
+#[test]
+fn newlines_in_spans_start_new_lines() {
+    let lines = vec![Line::from("one\ntwo".red()), Line::from("three")];
+    let (mut state, backend) = pager(lines, CommandList::default(), 20, 4);
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    let backend = backend.borrow();
+    assert_eq!(backend.screen()[..3], ["one", "two", "three"]);
+    assert_eq!(
+        backend.cell(0, 1).unwrap().style.foreground_color,
+        Some(Color::Red)
+    );
+}
+
+#[test]
+fn search_in_spans() {
+    let lines: Vec<Line> = (0..50)
+        .map(|i| Line::from(format!("{}", i).blue()))
+        .collect();
+    let (mut state, backend) = pager(lines, CommandList::default(), 20, 4);
+    press(&backend, "/42\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(backend.borrow().screen()[0], "42");
+}
+
+#[test]
+fn search_in_escape_sequences_of_spans() {
+    let mut lines: Vec<Line> = (0..30).map(|i| Line::from(format!("{}", i))).collect();
+    lines[20] = Line::from(Span::raw("\x1b[31mred"));
+    let (mut state, backend) = pager(lines, CommandList::default(), 20, 4);
+    press(&backend, "/31m\nq");
+    run(&mut state).unwrap();
+
+    assert_eq!(backend.borrow().screen()[0], "^[[31mred");
+}
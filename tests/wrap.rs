@@ -0,0 +1,131 @@
+mod common;
+
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+
+use common::{pager, press, status};
+use pager_rs::{run, CommandList, Line, Span};
+
+/// Lines that take 2 rows at a width of 10.
+fn content() -> String {
+    (0..20).map(|i| format!("{:02} aaaa bbbb\n", i)).collect()
+}
+
+#[test]
+fn long_lines_are_cut_without_wrap() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 10, 4);
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(
+        backend.borrow().screen()[..3],
+        ["00 aaaa bb", "01 aaaa bb", "02 aaaa bb"]
+    );
+}
+
+#[test]
+fn wrap_toggle() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 10, 4);
+    press(&backend, "w");
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(
+        backend.borrow().screen()[..3],
+        ["00 aaaa", "bbbb", "01 aaaa"]
+    );
+}
+
+#[test]
+fn scrolling_moves_by_rows() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 10, 4);
+    press(&backend, ":set wrap\n");
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Down, KeyModifiers::NONE);
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(backend.borrow().screen()[..3], ["bbbb", "01 aaaa", "bbbb"]);
+    assert!(status(&backend).ends_with("1/20 (5%)"));
+}
+
+#[test]
+fn end_shows_the_last_row_at_the_bottom() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 10, 4);
+    press(&backend, "wGq");
+    run(&mut state).unwrap();
+
+    assert_eq!(backend.borrow().screen()[..3], ["bbbb", "19 aaaa", "bbbb"]);
+}
+
+#[test]
+fn resize_keeps_the_top_line() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 10, 4);
+    press(&backend, "w:5\n");
+    backend.borrow_mut().push_event(Event::Resize(20, 4));
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    assert_eq!(
+        backend.borrow().screen()[..2],
+        ["04 aaaa bbbb", "05 aaaa bbbb"]
+    );
+}
+
+#[test]
+fn wrapped_row_is_clamped_after_resize() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 10, 4);
+    press(&backend, "w");
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Down, KeyModifiers::NONE);
+    backend.borrow_mut().push_event(Event::Resize(20, 4));
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    // The line takes one row at the new width, so it is shown from its start.
+    assert_eq!(backend.borrow().screen()[0], "00 aaaa bbbb");
+}
+
+#[test]
+fn rows_of_escape_sequences_in_spans() {
+    let lines = vec![Line::from(Span::raw("\x1b[31mred red")), Line::from("next")];
+    let (mut state, backend) = pager(lines, CommandList::default(), 10, 4);
+    press(&backend, "w");
+    backend
+        .borrow_mut()
+        .push_key(KeyCode::Down, KeyModifiers::NONE);
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    // `ESC` is shown as `^[`, so the line takes two rows.
+    assert_eq!(backend.borrow().screen()[..2], ["red", "next"]);
+}
+
+#[test]
+fn screen_without_content_rows() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 10, 0);
+    press(&backend, "wGq");
+    run(&mut state).unwrap();
+
+    assert!(backend.borrow().screen().is_empty());
+    assert_eq!(state.get_visible(), "");
+}
+
+#[test]
+fn resize_to_no_content_rows() {
+    let (mut state, backend) = pager(content(), CommandList::default(), 10, 4);
+    press(&backend, "w");
+    backend.borrow_mut().push_event(Event::Resize(10, 1));
+    press(&backend, "G");
+    backend.borrow_mut().push_event(Event::Resize(10, 0));
+    press(&backend, "G");
+    backend.borrow_mut().push_event(Event::Resize(0, 0));
+    press(&backend, "G");
+    backend.borrow_mut().push_event(Event::Resize(10, 4));
+    press(&backend, "q");
+    run(&mut state).unwrap();
+
+    // The last row is at the top, since there was no row above it.
+    assert_eq!(backend.borrow().screen()[..3], ["bbbb", "", ""]);
+}